@@ -1,6 +1,6 @@
 use super::{modules::cpu::CPUs, modules::memory::Memory};
 use crate::{
-    modules::{misc::Misc, network::Network, process::Processes},
+    modules::{container::EffectiveResources, misc::Misc, network::Network, process::Processes},
     parser::Parser,
     timestamp,
 };
@@ -16,17 +16,27 @@ pub struct Data {
     pub processes: Option<Processes>,
     pub misc: Option<Misc>,
     pub network: Option<Network>,
+    /// Resources available to this process, within the limits of its cgroup.
+    pub effective: Option<EffectiveResources>,
 }
 
 impl Data {
     pub fn new() -> Data {
+        let cpu = CPUs::parse().ok();
+        let memory = Memory::parse().ok();
+        let effective = memory
+            .as_ref()
+            .zip(cpu.as_ref())
+            .and_then(|(memory, cpu)| EffectiveResources::new(memory, cpu));
+
         Data {
             timestamp: timestamp!(),
-            cpu: CPUs::parse().ok(),
-            memory: Memory::parse().ok(),
+            cpu,
+            memory,
             processes: Processes::parse().ok(),
             misc: Misc::parse().ok(),
             network: Network::parse().ok(),
+            effective,
         }
     }
 }
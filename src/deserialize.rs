@@ -76,7 +76,7 @@ impl<'de> Deserializer<'de> {
     }
 }
 
-pub fn from_str<'a, T: Deserialize<'a>>(s: &'a str) -> Result<T, DeError>
+pub fn from_str<'a, T>(s: &'a str) -> Result<T, DeError>
 where
     T: Deserialize<'a>,
 {
@@ -84,7 +84,7 @@ where
 
     match T::deserialize(&mut deserializer) {
         Ok(t) => {
-            if deserializer.input.len() == 0 {
+            if deserializer.input.is_empty() {
                 Ok(t)
             } else {
                 Err(DeError::TrailingCharacters)
@@ -158,7 +158,7 @@ impl<'de, 'a> MapAccess<'de> for Format<'a, 'de> {
 }
 
 // actual deserialization
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = serde::de::value::Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
@@ -411,6 +411,7 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     }
 }
 
+#[cfg(test)]
 mod test {
     use super::*;
 
@@ -8,3 +8,83 @@ macro_rules! timestamp {
             .as_millis()
     };
 }
+
+/// Read a "flat keyed" file, as found in cgroupfs (`cpu.stat`, `memory.stat`, ...),
+/// where every line is a `key value` pair.
+pub(crate) fn read_flat_keyed<P: AsRef<std::path::Path>>(
+    path: P,
+) -> Option<std::collections::HashMap<String, u64>> {
+    let content = std::fs::read_to_string(path).ok()?;
+
+    Some(
+        content
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once(' ')?;
+                Some((key.to_string(), value.trim().parse().ok()?))
+            })
+            .collect(),
+    )
+}
+
+/// Read a file holding a single value, as found in sysfs and procfs.
+///
+/// Some attributes fail to read when they are not applicable, e.g. the
+/// speed of a wireless interface, which gives `None`.
+pub(crate) fn read_value<T: std::str::FromStr>(path: impl AsRef<std::path::Path>) -> Option<T> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Read a file holding a single value, where `max` stands for "no limit".
+///
+/// Returns `Some(None)` if the value is `max`.
+pub(crate) fn read_limit<P: AsRef<std::path::Path>>(path: P) -> Option<Option<u64>> {
+    let content = std::fs::read_to_string(path).ok()?;
+
+    match content.trim() {
+        "max" => Some(None),
+        val => val.parse().ok().map(Some),
+    }
+}
+
+/// Size of a memory page in bytes.
+pub(crate) fn page_size() -> u64 {
+    // SAFETY: sysconf has no preconditions.
+    let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+
+    u64::try_from(size).unwrap_or(4096)
+}
+
+/// Format a point in time as an ISO 8601 UTC timestamp with millisecond precision,
+/// e.g. `2023-11-14T22:13:20.120Z`.
+pub(crate) fn iso8601(time: std::time::SystemTime) -> String {
+    let millis = time
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or_else(|e| -(e.duration().as_millis() as i64));
+
+    let secs = millis.div_euclid(1000);
+    let (days, secs_of_day) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    // Convert days since the epoch to a civil date, from Howard Hinnant's `civil_from_days`.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        millis.rem_euclid(1000)
+    )
+}
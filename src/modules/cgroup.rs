@@ -0,0 +1,523 @@
+use crate::{
+    helper::{read_flat_keyed, read_limit, read_value},
+    modules::process::{Process, PROC},
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+#[cfg(test)]
+pub(crate) const CGROUP_ROOT: &str = "./mock/sysfscgroup/";
+#[cfg(not(test))]
+pub(crate) const CGROUP_ROOT: &str = "/sys/fs/cgroup/";
+
+/// Contents of `cpu.stat`, times are in microseconds.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CpuStat {
+    usage_usec: u64,
+    user_usec: u64,
+    system_usec: u64,
+    /// Only present if the cpu controller is enabled.
+    nr_periods: Option<u64>,
+    nr_throttled: Option<u64>,
+    throttled_usec: Option<u64>,
+}
+
+/// Contents of `memory.events`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MemoryEvents {
+    low: u64,
+    high: u64,
+    max: u64,
+    oom: u64,
+    oom_kill: u64,
+    oom_group_kill: u64,
+}
+
+impl MemoryEvents {
+    pub fn low(&self) -> u64 {
+        self.low
+    }
+
+    pub fn high(&self) -> u64 {
+        self.high
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    pub fn oom(&self) -> u64 {
+        self.oom
+    }
+
+    pub fn oom_kill(&self) -> u64 {
+        self.oom_kill
+    }
+
+    pub fn oom_group_kill(&self) -> u64 {
+        self.oom_group_kill
+    }
+}
+
+/// Memory controller data, values are in bytes.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MemoryStat {
+    current: u64,
+    /// `None` if the file is missing, `Some(None)` if the group is not limited.
+    max: Option<Option<u64>>,
+    /// `None` if the file is missing, `Some(None)` if the group is not limited.
+    high: Option<Option<u64>>,
+    /// Raw contents of `memory.stat`.
+    stat: HashMap<String, u64>,
+    events: Option<MemoryEvents>,
+}
+
+/// A single device line of `io.stat`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct IoStat {
+    rbytes: u64,
+    wbytes: u64,
+    rios: u64,
+    wios: u64,
+    dbytes: u64,
+    dios: u64,
+}
+
+/// Pids controller data.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PidsStat {
+    current: u64,
+    /// `None` if the file is missing, `Some(None)` if the group is not limited.
+    max: Option<Option<u64>>,
+}
+
+/// A single line of a `*.pressure` file.
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PressureStat {
+    avg10: f32,
+    avg60: f32,
+    avg300: f32,
+    /// Total stall time in microseconds.
+    total: u64,
+}
+
+impl PressureStat {
+    pub fn avg10(&self) -> f32 {
+        self.avg10
+    }
+
+    pub fn avg60(&self) -> f32 {
+        self.avg60
+    }
+
+    pub fn avg300(&self) -> f32 {
+        self.avg300
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+/// Pressure Stall Information, refer to the kernel documentation `accounting/psi.rst`.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Pressure {
+    some: Option<PressureStat>,
+    full: Option<PressureStat>,
+}
+
+/// A node of the cgroup v2 hierarchy mounted at `/sys/fs/cgroup`.
+///
+/// Parsing walks the whole hierarchy, so it is not part of
+/// [Data](crate::data::Data).
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CGroup {
+    /// Path of the group relative to the cgroup root, e.g. `/system.slice/sshd.service`.
+    path: String,
+    /// Member pids, read from `cgroup.procs`.
+    procs: Vec<u32>,
+    cpu: Option<CpuStat>,
+    memory: Option<MemoryStat>,
+    /// `io.stat` indexed by `major:minor` device number.
+    io: HashMap<String, IoStat>,
+    pids: Option<PidsStat>,
+    /// Pressure files indexed by resource (`cpu`, `memory`, `io`, `irq`).
+    pressure: HashMap<String, Pressure>,
+    children: Vec<CGroup>,
+}
+
+impl Parser for CGroup {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        let root = Path::new(CGROUP_ROOT);
+
+        if !root.join("cgroup.procs").exists() {
+            return Err(DataError::FileNotFound);
+        }
+
+        Ok(CGroup::parse_dir(root, "/"))
+    }
+}
+
+impl CGroup {
+    fn parse_dir(dir: &Path, path: &str) -> CGroup {
+        let procs = fs::read_to_string(dir.join("cgroup.procs"))
+            .map(|s| s.lines().filter_map(|l| l.trim().parse().ok()).collect())
+            .unwrap_or_default();
+
+        let pressure = ["cpu", "memory", "io", "irq"]
+            .iter()
+            .filter_map(|res| {
+                Pressure::parse(dir.join(format!("{}.pressure", res))).map(|p| (res.to_string(), p))
+            })
+            .collect();
+
+        let mut children: Vec<CGroup> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_type().is_ok_and(|t| t.is_dir()))
+                    .filter_map(|entry| {
+                        let name = entry.file_name().to_str()?.to_string();
+                        let child_path = format!("{}/{}", path.trim_end_matches('/'), name);
+
+                        Some(CGroup::parse_dir(&entry.path(), &child_path))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        children.sort_by(|a, b| a.path.cmp(&b.path));
+
+        CGroup {
+            path: path.to_string(),
+            procs,
+            cpu: CpuStat::parse(dir),
+            memory: MemoryStat::parse(dir),
+            io: IoStat::parse(dir),
+            pids: PidsStat::parse(dir),
+            pressure,
+            children,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn procs(&self) -> &[u32] {
+        &self.procs
+    }
+
+    pub fn cpu(&self) -> Option<&CpuStat> {
+        self.cpu.as_ref()
+    }
+
+    pub fn memory(&self) -> Option<&MemoryStat> {
+        self.memory.as_ref()
+    }
+
+    /// `io.stat` indexed by `major:minor` device number.
+    pub fn io(&self) -> &HashMap<String, IoStat> {
+        &self.io
+    }
+
+    pub fn pids(&self) -> Option<&PidsStat> {
+        self.pids.as_ref()
+    }
+
+    /// Pressure Stall Information of a resource, e.g. `memory`.
+    pub fn pressure(&self, resource: &str) -> Option<&Pressure> {
+        self.pressure.get(resource)
+    }
+
+    pub fn children(&self) -> &[CGroup] {
+        &self.children
+    }
+
+    /// Iterate over this group and all of its descendants, depth first.
+    pub fn iter(&self) -> impl Iterator<Item = &CGroup> {
+        let mut stack = vec![self];
+
+        std::iter::from_fn(move || {
+            let next = stack.pop()?;
+            stack.extend(next.children.iter().rev());
+            Some(next)
+        })
+    }
+
+    /// Find a group by its path relative to the cgroup root.
+    pub fn find(&self, path: &str) -> Option<&CGroup> {
+        let path = path.trim_end_matches('/');
+        let path = if path.is_empty() { "/" } else { path };
+
+        self.iter().find(|group| group.path == path)
+    }
+
+    /// Retrieve the members of this group from a list of processes.
+    pub fn processes<'a>(&self, processes: &'a [Process]) -> Vec<&'a Process> {
+        processes
+            .iter()
+            .filter(|process| self.procs.contains(&process.pid()))
+            .collect()
+    }
+
+    /// Read the cgroup v2 path of a process from `/proc/[pid]/cgroup`.
+    pub fn path_of(pid: u32) -> Option<String> {
+        fs::read_to_string(format!("{}{}/cgroup", PROC, pid))
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("0::").map(|s| s.to_string()))
+    }
+
+    /// Retrieve the systemd unit (service or scope) a process belongs to.
+    pub fn unit_of(pid: u32) -> Option<String> {
+        CGroup::path_of(pid)?
+            .rsplit('/')
+            .find(|name| name.ends_with(".service") || name.ends_with(".scope"))
+            .map(|name| name.to_string())
+    }
+}
+
+impl CpuStat {
+    fn parse(dir: &Path) -> Option<CpuStat> {
+        let stat = read_flat_keyed(dir.join("cpu.stat"))?;
+
+        Some(CpuStat {
+            usage_usec: *stat.get("usage_usec")?,
+            user_usec: *stat.get("user_usec")?,
+            system_usec: *stat.get("system_usec")?,
+            nr_periods: stat.get("nr_periods").copied(),
+            nr_throttled: stat.get("nr_throttled").copied(),
+            throttled_usec: stat.get("throttled_usec").copied(),
+        })
+    }
+
+    pub fn usage_usec(&self) -> u64 {
+        self.usage_usec
+    }
+
+    pub fn user_usec(&self) -> u64 {
+        self.user_usec
+    }
+
+    pub fn system_usec(&self) -> u64 {
+        self.system_usec
+    }
+
+    pub fn nr_periods(&self) -> Option<u64> {
+        self.nr_periods
+    }
+
+    pub fn nr_throttled(&self) -> Option<u64> {
+        self.nr_throttled
+    }
+
+    pub fn throttled_usec(&self) -> Option<u64> {
+        self.throttled_usec
+    }
+}
+
+impl MemoryStat {
+    fn parse(dir: &Path) -> Option<MemoryStat> {
+        let current = read_value(dir.join("memory.current"))?;
+
+        let events = read_flat_keyed(dir.join("memory.events")).map(|events| MemoryEvents {
+            low: events.get("low").copied().unwrap_or_default(),
+            high: events.get("high").copied().unwrap_or_default(),
+            max: events.get("max").copied().unwrap_or_default(),
+            oom: events.get("oom").copied().unwrap_or_default(),
+            oom_kill: events.get("oom_kill").copied().unwrap_or_default(),
+            oom_group_kill: events.get("oom_group_kill").copied().unwrap_or_default(),
+        });
+
+        Some(MemoryStat {
+            current,
+            max: read_limit(dir.join("memory.max")),
+            high: read_limit(dir.join("memory.high")),
+            stat: read_flat_keyed(dir.join("memory.stat")).unwrap_or_default(),
+            events,
+        })
+    }
+
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    pub fn max(&self) -> Option<Option<u64>> {
+        self.max
+    }
+
+    pub fn high(&self) -> Option<Option<u64>> {
+        self.high
+    }
+
+    pub fn stat(&self) -> &HashMap<String, u64> {
+        &self.stat
+    }
+
+    pub fn events(&self) -> Option<&MemoryEvents> {
+        self.events.as_ref()
+    }
+}
+
+impl IoStat {
+    fn parse(dir: &Path) -> HashMap<String, IoStat> {
+        let Ok(content) = fs::read_to_string(dir.join("io.stat")) else {
+            return HashMap::new();
+        };
+
+        content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace();
+                let device = fields.next()?.to_string();
+                let mut stat = IoStat::default();
+
+                for (key, value) in fields.filter_map(|f| f.split_once('=')) {
+                    let value = value.parse().unwrap_or_default();
+
+                    match key {
+                        "rbytes" => stat.rbytes = value,
+                        "wbytes" => stat.wbytes = value,
+                        "rios" => stat.rios = value,
+                        "wios" => stat.wios = value,
+                        "dbytes" => stat.dbytes = value,
+                        "dios" => stat.dios = value,
+                        _ => (),
+                    }
+                }
+
+                Some((device, stat))
+            })
+            .collect()
+    }
+
+    pub fn rbytes(&self) -> u64 {
+        self.rbytes
+    }
+
+    pub fn wbytes(&self) -> u64 {
+        self.wbytes
+    }
+
+    pub fn rios(&self) -> u64 {
+        self.rios
+    }
+
+    pub fn wios(&self) -> u64 {
+        self.wios
+    }
+
+    pub fn dbytes(&self) -> u64 {
+        self.dbytes
+    }
+
+    pub fn dios(&self) -> u64 {
+        self.dios
+    }
+}
+
+impl PidsStat {
+    fn parse(dir: &Path) -> Option<PidsStat> {
+        let current = read_value(dir.join("pids.current"))?;
+
+        Some(PidsStat {
+            current,
+            max: read_limit(dir.join("pids.max")),
+        })
+    }
+
+    pub fn current(&self) -> u64 {
+        self.current
+    }
+
+    pub fn max(&self) -> Option<Option<u64>> {
+        self.max
+    }
+}
+
+impl Pressure {
+    fn parse<P: AsRef<Path>>(path: P) -> Option<Pressure> {
+        let content = fs::read_to_string(path).ok()?;
+        let mut pressure = Pressure {
+            some: None,
+            full: None,
+        };
+
+        for line in content.lines() {
+            let mut fields = line.split_whitespace();
+            let kind = fields.next();
+            let mut stat = PressureStat::default();
+
+            for (key, value) in fields.filter_map(|f| f.split_once('=')) {
+                match key {
+                    "avg10" => stat.avg10 = value.parse().unwrap_or_default(),
+                    "avg60" => stat.avg60 = value.parse().unwrap_or_default(),
+                    "avg300" => stat.avg300 = value.parse().unwrap_or_default(),
+                    "total" => stat.total = value.parse().unwrap_or_default(),
+                    _ => (),
+                }
+            }
+
+            match kind {
+                Some("some") => pressure.some = Some(stat),
+                Some("full") => pressure.full = Some(stat),
+                _ => (),
+            }
+        }
+
+        Some(pressure)
+    }
+
+    pub fn some(&self) -> Option<&PressureStat> {
+        self.some.as_ref()
+    }
+
+    pub fn full(&self) -> Option<&PressureStat> {
+        self.full.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let root = CGroup::parse().unwrap();
+
+        assert_eq!(root.iter().count(), 3);
+        assert!(root.memory().is_none());
+        assert_eq!(
+            root.pressure("cpu")
+                .and_then(Pressure::full)
+                .map(PressureStat::total),
+            Some(0)
+        );
+        assert!(root.pressure("irq").is_none());
+
+        let slice = root.find("/system.slice").unwrap();
+        let memory = slice.memory().unwrap();
+        assert_eq!(memory.max(), Some(None));
+        assert_eq!(memory.events().map(MemoryEvents::oom_kill), Some(2));
+        assert_eq!(slice.pids().and_then(PidsStat::max), Some(Some(4915)));
+
+        let service = root.find("/system.slice/sshd.service").unwrap();
+        assert_eq!(service.procs(), &[1021, 1022]);
+        assert_eq!(
+            service.memory().and_then(MemoryStat::max),
+            Some(Some(536870912))
+        );
+        assert_eq!(service.cpu().and_then(CpuStat::nr_throttled), Some(12));
+        assert_eq!(service.io()["8:0"].rbytes(), 4096);
+    }
+
+    #[test]
+    fn test_path_of() {
+        assert_eq!(CGroup::path_of(1).as_deref(), Some("/init.scope"));
+        assert_eq!(CGroup::unit_of(1).as_deref(), Some("init.scope"));
+    }
+}
@@ -0,0 +1,322 @@
+use crate::{
+    helper::{read_limit, read_value},
+    modules::{cgroup::CGROUP_ROOT, cpu::CPUs, memory::Memory, process::PROC},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Value of `memory.limit_in_bytes` when cgroup v1 does not limit a group.
+const V1_UNLIMITED: u64 = 0x7FFF_FFFF_FFFF_F000;
+
+/// Limits of a cgroup, as read from the cgroup filesystem.
+#[derive(Debug, Default, PartialEq)]
+struct Limits {
+    /// Hard memory limit in bytes.
+    memory_max: Option<u64>,
+    /// Memory throttling threshold in bytes.
+    memory_high: Option<u64>,
+    /// Memory currently charged to the group in bytes.
+    memory_usage: Option<u64>,
+    cpu_quota: Option<u64>,
+    cpu_period: Option<u64>,
+}
+
+impl Limits {
+    /// Read the limits of a cgroup v2 group, limits of the ancestors also apply.
+    fn v2(root: &Path, group: &str) -> Limits {
+        let mut dir = root.join(group.trim_start_matches('/'));
+
+        // Inside a cgroup namespace the group is mounted as the root.
+        if !dir.exists() {
+            dir = root.to_path_buf();
+        }
+
+        let mut limits = Limits {
+            memory_usage: read_value(dir.join("memory.current")),
+            ..Default::default()
+        };
+
+        for dir in dir.ancestors() {
+            limits.memory_max = min(
+                limits.memory_max,
+                read_limit(dir.join("memory.max")).flatten(),
+            );
+            limits.memory_high = min(
+                limits.memory_high,
+                read_limit(dir.join("memory.high")).flatten(),
+            );
+
+            if let Some((quota, period)) = read_cpu_max(dir.join("cpu.max")) {
+                let current = limits
+                    .cpu_quota
+                    .zip(limits.cpu_period)
+                    .map(|(q, p)| q as f64 / p as f64);
+
+                if current.is_none_or(|c| (quota as f64 / period as f64) < c) {
+                    limits.cpu_quota = Some(quota);
+                    limits.cpu_period = Some(period);
+                }
+            }
+
+            if dir == root {
+                break;
+            }
+        }
+
+        limits
+    }
+
+    /// Read the limits of cgroup v1 groups, indexed by controller.
+    fn v1(root: &Path, groups: &HashMap<String, String>) -> Limits {
+        let dir = |controller: &str| {
+            let (mount, group) = groups
+                .iter()
+                .find(|(controllers, _)| controllers.split(',').any(|c| c == controller))?;
+
+            [root.join(mount), root.join(controller)]
+                .into_iter()
+                .find(|mount| mount.exists())
+                .map(|mount| {
+                    let dir = mount.join(group.trim_start_matches('/'));
+                    if dir.exists() {
+                        dir
+                    } else {
+                        mount
+                    }
+                })
+        };
+
+        let mut limits = Limits::default();
+
+        if let Some(dir) = dir("memory") {
+            limits.memory_max =
+                read_value(dir.join("memory.limit_in_bytes")).filter(|limit| *limit < V1_UNLIMITED);
+            limits.memory_high = read_value(dir.join("memory.soft_limit_in_bytes"))
+                .filter(|limit| *limit < V1_UNLIMITED);
+            limits.memory_usage = read_value(dir.join("memory.usage_in_bytes"));
+        }
+
+        if let Some(dir) = dir("cpu") {
+            let quota = fs::read_to_string(dir.join("cpu.cfs_quota_us"))
+                .ok()
+                .and_then(|s| s.trim().parse::<i64>().ok())
+                .and_then(|quota| u64::try_from(quota).ok());
+
+            if let Some(quota) = quota {
+                limits.cpu_quota = Some(quota);
+                limits.cpu_period = read_value(dir.join("cpu.cfs_period_us"));
+            }
+        }
+
+        limits
+    }
+}
+
+/// Resources available to the current process, taking the limits of
+/// its cgroup (e.g. a container) into account.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EffectiveResources {
+    /// Version of the cgroup hierarchy the limits were read from.
+    cgroup_version: u8,
+    /// Memory of the host in bytes.
+    host_memory_total: u64,
+    /// Memory used on the host in bytes.
+    host_memory_used: u64,
+    host_memory_usage_percent: f32,
+    /// Number of CPUs of the host.
+    host_cpus: usize,
+    /// Hard memory limit of the cgroup in bytes, `None` if unlimited.
+    memory_limit: Option<u64>,
+    /// Memory throttling threshold of the cgroup in bytes, `None` if unlimited.
+    memory_high: Option<u64>,
+    /// Memory usable by the process in bytes: the host memory capped by [Self::memory_limit].
+    memory_total: u64,
+    /// Memory charged to the cgroup in bytes.
+    memory_used: Option<u64>,
+    /// [Self::memory_used] relative to [Self::memory_total].
+    memory_usage_percent: Option<f32>,
+    /// CPU time in microseconds the group may use every [Self::cpu_period].
+    cpu_quota: Option<u64>,
+    cpu_period: Option<u64>,
+    /// CPUs usable by the process: the host CPUs capped by the CPU quota.
+    cpus: f32,
+}
+
+impl EffectiveResources {
+    /// Compute the resources available to the current process from the host values.
+    pub fn new(memory: &Memory, cpus: &CPUs) -> Option<EffectiveResources> {
+        let content = fs::read_to_string(format!("{}self/cgroup", PROC)).ok()?;
+        let root = Path::new(CGROUP_ROOT);
+
+        let v2 = content.lines().find_map(|line| line.strip_prefix("0::"));
+
+        let (version, limits) = match v2 {
+            Some(group) if root.join("cgroup.controllers").exists() => (2, Limits::v2(root, group)),
+            _ => {
+                let groups = content
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.splitn(3, ':').skip(1);
+                        Some((fields.next()?.to_string(), fields.next()?.to_string()))
+                    })
+                    .filter(|(controllers, _)| !controllers.is_empty())
+                    .collect();
+
+                (1, Limits::v1(root, &groups))
+            }
+        };
+
+        Some(EffectiveResources::from_limits(
+            version, limits, memory, cpus,
+        ))
+    }
+
+    fn from_limits(version: u8, limits: Limits, memory: &Memory, cpus: &CPUs) -> Self {
+        let host_memory_total = memory.total() as u64 * 1024;
+        let host_memory_used = host_memory_total - memory.available() as u64 * 1024;
+        let memory_total = limits
+            .memory_max
+            .map_or(host_memory_total, |max| max.min(host_memory_total));
+
+        let host_cpus = cpus.len();
+        let quota_cpus = limits
+            .cpu_quota
+            .zip(limits.cpu_period)
+            .map(|(quota, period)| quota as f32 / period as f32);
+
+        EffectiveResources {
+            cgroup_version: version,
+            host_memory_total,
+            host_memory_used,
+            host_memory_usage_percent: percent(host_memory_used, host_memory_total),
+            host_cpus,
+            memory_limit: limits.memory_max,
+            memory_high: limits.memory_high,
+            memory_total,
+            memory_used: limits.memory_usage,
+            memory_usage_percent: limits.memory_usage.map(|used| percent(used, memory_total)),
+            cpu_quota: limits.cpu_quota,
+            cpu_period: limits.cpu_period,
+            cpus: quota_cpus.map_or(host_cpus as f32, |cpus| cpus.min(host_cpus as f32)),
+        }
+    }
+
+    pub fn cgroup_version(&self) -> u8 {
+        self.cgroup_version
+    }
+
+    pub fn host_memory_total(&self) -> u64 {
+        self.host_memory_total
+    }
+
+    pub fn host_memory_used(&self) -> u64 {
+        self.host_memory_used
+    }
+
+    pub fn host_memory_usage_percent(&self) -> f32 {
+        self.host_memory_usage_percent
+    }
+
+    pub fn host_cpus(&self) -> usize {
+        self.host_cpus
+    }
+
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+
+    pub fn memory_high(&self) -> Option<u64> {
+        self.memory_high
+    }
+
+    pub fn memory_total(&self) -> u64 {
+        self.memory_total
+    }
+
+    pub fn memory_used(&self) -> Option<u64> {
+        self.memory_used
+    }
+
+    pub fn memory_usage_percent(&self) -> Option<f32> {
+        self.memory_usage_percent
+    }
+
+    pub fn cpu_quota(&self) -> Option<u64> {
+        self.cpu_quota
+    }
+
+    pub fn cpu_period(&self) -> Option<u64> {
+        self.cpu_period
+    }
+
+    pub fn cpus(&self) -> f32 {
+        self.cpus
+    }
+}
+
+fn percent(part: u64, total: u64) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 / total as f32 * 100.0
+    }
+}
+
+fn min(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Read `cpu.max`, which contains `$MAX $PERIOD` where `$MAX` may be `max`.
+fn read_cpu_max<P: AsRef<Path>>(path: P) -> Option<(u64, u64)> {
+    let content = fs::read_to_string(path).ok()?;
+    let (quota, period) = content.trim().split_once(' ')?;
+
+    Some((quota.parse().ok()?, period.parse().ok()?))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_effective_v2() {
+        let res =
+            EffectiveResources::new(&Memory::parse().unwrap(), &CPUs::parse().unwrap()).unwrap();
+
+        assert_eq!(res.cgroup_version, 2);
+        assert_eq!(res.memory_limit, Some(536870912));
+        assert_eq!(res.memory_total, 536870912);
+        assert_eq!(res.memory_used, Some(8388608));
+        assert_eq!(res.memory_usage_percent, Some(1.5625));
+        assert_eq!(res.cpus, 0.5);
+        assert_eq!(res.memory_limit(), Some(536870912));
+        assert_eq!(res.memory_usage_percent(), Some(1.5625));
+        assert_eq!(res.cpu_quota().zip(res.cpu_period()), Some((50000, 100000)));
+        assert_eq!(res.host_cpus(), CPUs::parse().unwrap().len());
+    }
+
+    #[test]
+    fn test_limits_v1() {
+        let groups = HashMap::from([
+            ("memory".to_string(), "/docker/4f2a".to_string()),
+            ("cpu,cpuacct".to_string(), "/docker/4f2a".to_string()),
+        ]);
+
+        let limits = Limits::v1(Path::new("./mock/sysfscgroupv1/"), &groups);
+
+        assert_eq!(
+            limits,
+            Limits {
+                memory_max: Some(1073741824),
+                memory_high: None,
+                memory_usage: Some(268435456),
+                cpu_quota: Some(150000),
+                cpu_period: Some(100000),
+            }
+        );
+    }
+}
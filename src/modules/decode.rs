@@ -0,0 +1,399 @@
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+/// Number of the first real-time signal, as reported by the kernel.
+const SIGRTMIN: u8 = 32;
+/// Number of the last real-time signal.
+const SIGRTMAX: u8 = 64;
+
+/// A signal number, refer to `signal(7)`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    SIGHUP,
+    SIGINT,
+    SIGQUIT,
+    SIGILL,
+    SIGTRAP,
+    SIGABRT,
+    SIGBUS,
+    SIGFPE,
+    SIGKILL,
+    SIGUSR1,
+    SIGSEGV,
+    SIGUSR2,
+    SIGPIPE,
+    SIGALRM,
+    SIGTERM,
+    SIGSTKFLT,
+    SIGCHLD,
+    SIGCONT,
+    SIGSTOP,
+    SIGTSTP,
+    SIGTTIN,
+    SIGTTOU,
+    SIGURG,
+    SIGXCPU,
+    SIGXFSZ,
+    SIGVTALRM,
+    SIGPROF,
+    SIGWINCH,
+    SIGIO,
+    SIGPWR,
+    SIGSYS,
+    /// Real-time signal `SIGRTMIN + n`, where `n` is at most `SIGRTMAX - SIGRTMIN` (32).
+    SIGRT(u8),
+}
+
+impl Signal {
+    const STANDARD: [Signal; 31] = [
+        Signal::SIGHUP,
+        Signal::SIGINT,
+        Signal::SIGQUIT,
+        Signal::SIGILL,
+        Signal::SIGTRAP,
+        Signal::SIGABRT,
+        Signal::SIGBUS,
+        Signal::SIGFPE,
+        Signal::SIGKILL,
+        Signal::SIGUSR1,
+        Signal::SIGSEGV,
+        Signal::SIGUSR2,
+        Signal::SIGPIPE,
+        Signal::SIGALRM,
+        Signal::SIGTERM,
+        Signal::SIGSTKFLT,
+        Signal::SIGCHLD,
+        Signal::SIGCONT,
+        Signal::SIGSTOP,
+        Signal::SIGTSTP,
+        Signal::SIGTTIN,
+        Signal::SIGTTOU,
+        Signal::SIGURG,
+        Signal::SIGXCPU,
+        Signal::SIGXFSZ,
+        Signal::SIGVTALRM,
+        Signal::SIGPROF,
+        Signal::SIGWINCH,
+        Signal::SIGIO,
+        Signal::SIGPWR,
+        Signal::SIGSYS,
+    ];
+
+    /// Create a signal from its number, starting at 1 for `SIGHUP`.
+    pub fn from_number(number: u8) -> Option<Signal> {
+        match number {
+            1..=31 => Some(Signal::STANDARD[number as usize - 1]),
+            SIGRTMIN..=SIGRTMAX => Some(Signal::SIGRT(number - SIGRTMIN)),
+            _ => None,
+        }
+    }
+
+    /// Number of the signal, `None` for a real-time signal past `SIGRTMAX`.
+    pub fn number(&self) -> Option<u8> {
+        match self {
+            Signal::SIGRT(n) => SIGRTMIN.checked_add(*n).filter(|n| *n <= SIGRTMAX),
+            signal => Signal::STANDARD
+                .iter()
+                .position(|s| s == signal)
+                .map(|position| position as u8 + 1),
+        }
+    }
+}
+
+/// A set of signals, such as the pending, blocked, ignored and caught
+/// signals of `/proc/[pid]/stat`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignalSet {
+    /// Bitmask where bit `n - 1` stands for signal `n`.
+    raw: u64,
+    signals: Vec<Signal>,
+}
+
+impl From<u64> for SignalSet {
+    fn from(raw: u64) -> Self {
+        let signals = (1..=64)
+            .filter(|n| raw & (1 << (n - 1)) != 0)
+            .filter_map(Signal::from_number)
+            .collect();
+
+        SignalSet { raw, signals }
+    }
+}
+
+impl SignalSet {
+    pub fn raw(&self) -> u64 {
+        self.raw
+    }
+
+    pub fn signals(&self) -> &[Signal] {
+        &self.signals
+    }
+
+    /// Whether the signal is in the set, `false` for an invalid signal.
+    pub fn contains(&self, signal: Signal) -> bool {
+        signal
+            .number()
+            .and_then(|number| 1u64.checked_shl(number as u32 - 1))
+            .is_some_and(|bit| self.raw & bit != 0)
+    }
+}
+
+bitflags! {
+    /// The `PF_*` flags of a task, defined in `include/linux/sched.h`.
+    ///
+    /// Details depend on the kernel version, unknown bits are retained.
+    #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct ProcessFlags: u32 {
+        /// I'm a virtual CPU.
+        const VCPU = 0x0000_0001;
+        /// I am an IDLE thread.
+        const IDLE = 0x0000_0002;
+        /// Getting shut down.
+        const EXITING = 0x0000_0004;
+        /// Coredumps should ignore this task.
+        const POSTCOREDUMP = 0x0000_0008;
+        /// Task is an IO worker.
+        const IO_WORKER = 0x0000_0010;
+        /// I'm a workqueue worker.
+        const WQ_WORKER = 0x0000_0020;
+        /// Forked but didn't exec.
+        const FORKNOEXEC = 0x0000_0040;
+        /// Process policy on mce errors.
+        const MCE_PROCESS = 0x0000_0080;
+        /// Used super-user privileges.
+        const SUPERPRIV = 0x0000_0100;
+        /// Dumped core.
+        const DUMPCORE = 0x0000_0200;
+        /// Killed by a signal.
+        const SIGNALED = 0x0000_0400;
+        /// Allocating memory to free memory.
+        const MEMALLOC = 0x0000_0800;
+        /// set_user() noticed that RLIMIT_NPROC was exceeded.
+        const NPROC_EXCEEDED = 0x0000_1000;
+        /// If unset the fpu must be initialized before use.
+        const USED_MATH = 0x0000_2000;
+        /// Kernel thread cloned from userspace thread.
+        const USER_WORKER = 0x0000_4000;
+        /// This thread should not be frozen.
+        const NOFREEZE = 0x0000_8000;
+        /// I am kswapd.
+        const KSWAPD = 0x0002_0000;
+        /// All allocations inherit GFP_NOFS.
+        const MEMALLOC_NOFS = 0x0004_0000;
+        /// All allocations inherit GFP_NOIO.
+        const MEMALLOC_NOIO = 0x0008_0000;
+        /// Throttle writes only against the bdi I write to.
+        const LOCAL_THROTTLE = 0x0010_0000;
+        /// I am a kernel thread.
+        const KTHREAD = 0x0020_0000;
+        /// Randomize virtual address space.
+        const RANDOMIZE = 0x0040_0000;
+        /// Userland is not allowed to meddle with cpus_mask.
+        const NO_SETAFFINITY = 0x0400_0000;
+        /// Early kill for mce process policy.
+        const MCE_EARLY = 0x0800_0000;
+        /// Allocations constrained to zones which allow long term pinning.
+        const MEMALLOC_PIN = 0x1000_0000;
+        /// Plug has ts that needs updating.
+        const BLOCK_TS = 0x2000_0000;
+        /// This thread called freeze_processes() and should not be frozen.
+        const SUSPEND_TASK = 0x8000_0000;
+
+        const _ = !0;
+    }
+}
+
+/// The kernel flags word of a process, raw and decoded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flags {
+    raw: u32,
+    flags: ProcessFlags,
+}
+
+impl From<u32> for Flags {
+    fn from(raw: u32) -> Self {
+        Flags {
+            raw,
+            flags: ProcessFlags::from_bits_retain(raw),
+        }
+    }
+}
+
+impl Flags {
+    pub fn raw(&self) -> u32 {
+        self.raw
+    }
+
+    pub fn flags(&self) -> ProcessFlags {
+        self.flags
+    }
+}
+
+/// The controlling terminal of a process.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Tty {
+    /// The minor device number is contained in the combination of bits
+    /// 31 to 20 and 7 to 0; the major device number is in bits 15 to 8.
+    raw: u32,
+    major: u32,
+    minor: u32,
+    /// Name of the device in `/dev`, e.g. `pts/3`, if known.
+    name: Option<String>,
+}
+
+impl From<u32> for Tty {
+    fn from(raw: u32) -> Self {
+        let major = (raw >> 8) & 0xfff;
+        let minor = (raw & 0xff) | ((raw >> 12) & 0xfff00);
+
+        let name = match (major, minor) {
+            (0, 0) => None,
+            (4, 0..=63) => Some(format!("tty{}", minor)),
+            (4, _) => Some(format!("ttyS{}", minor - 64)),
+            (5, 0) => Some("tty".to_string()),
+            (5, 1) => Some("console".to_string()),
+            (5, 2) => Some("ptmx".to_string()),
+            (136..=143, _) => Some(format!("pts/{}", (major - 136) * 256 + minor)),
+            (166, _) => Some(format!("ttyACM{}", minor)),
+            (188, _) => Some(format!("ttyUSB{}", minor)),
+            (204, 64..) => Some(format!("ttyAMA{}", minor - 64)),
+            _ => None,
+        };
+
+        Tty {
+            raw,
+            major,
+            minor,
+            name,
+        }
+    }
+}
+
+impl Tty {
+    pub fn raw(&self) -> u32 {
+        self.raw
+    }
+
+    /// Whether the process has a controlling terminal.
+    pub fn is_some(&self) -> bool {
+        self.raw != 0
+    }
+
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+/// Scheduling policies, refer to `sched(7)`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchedPolicy {
+    #[serde(rename = "SCHED_OTHER")]
+    Other,
+    #[serde(rename = "SCHED_FIFO")]
+    Fifo,
+    #[serde(rename = "SCHED_RR")]
+    RoundRobin,
+    #[serde(rename = "SCHED_BATCH")]
+    Batch,
+    #[serde(rename = "SCHED_IDLE")]
+    Idle,
+    #[serde(rename = "SCHED_DEADLINE")]
+    Deadline,
+}
+
+/// The scheduling policy of a process, raw and decoded.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    raw: u32,
+    /// `None` if the policy is unknown to this crate.
+    policy: Option<SchedPolicy>,
+}
+
+impl From<u32> for Policy {
+    fn from(raw: u32) -> Self {
+        let policy = match raw {
+            0 => Some(SchedPolicy::Other),
+            1 => Some(SchedPolicy::Fifo),
+            2 => Some(SchedPolicy::RoundRobin),
+            3 => Some(SchedPolicy::Batch),
+            5 => Some(SchedPolicy::Idle),
+            6 => Some(SchedPolicy::Deadline),
+            _ => None,
+        };
+
+        Policy { raw, policy }
+    }
+}
+
+impl Policy {
+    pub fn raw(&self) -> u32 {
+        self.raw
+    }
+
+    pub fn policy(&self) -> Option<SchedPolicy> {
+        self.policy
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_signal_set() {
+        let set = SignalSet::from(671173123);
+
+        assert_eq!(
+            set.signals(),
+            [
+                Signal::SIGHUP,
+                Signal::SIGINT,
+                Signal::SIGUSR1,
+                Signal::SIGUSR2,
+                Signal::SIGTERM,
+                Signal::SIGCHLD,
+                Signal::SIGWINCH,
+                Signal::SIGPWR
+            ]
+        );
+        assert!(set.contains(Signal::SIGPWR));
+        assert!(!set.contains(Signal::SIGKILL));
+        assert_eq!(SignalSet::from(1 << 33).signals(), [Signal::SIGRT(2)]);
+        assert!(SignalSet::from(1 << 63).contains(Signal::SIGRT(32)));
+        assert!(!SignalSet::from(u64::MAX).contains(Signal::SIGRT(33)));
+        assert!(!SignalSet::from(u64::MAX).contains(Signal::SIGRT(255)));
+    }
+
+    #[test]
+    fn test_flags() {
+        let flags = Flags::from(69238880).flags();
+
+        assert!(flags.contains(ProcessFlags::KTHREAD | ProcessFlags::WQ_WORKER));
+        assert!(!flags.contains(ProcessFlags::EXITING));
+        assert_eq!(Flags::from(0x0100_0000).flags().bits(), 0x0100_0000);
+    }
+
+    #[test]
+    fn test_tty() {
+        let tty = Tty::from(34819);
+
+        assert_eq!((tty.major, tty.minor), (136, 3));
+        assert_eq!(tty.name(), Some("pts/3"));
+        assert_eq!(Tty::from(4229121).name(), Some("pts/1025"));
+        assert_eq!(Tty::from(0).name(), None);
+    }
+
+    #[test]
+    fn test_policy() {
+        assert_eq!(Policy::from(2).policy(), Some(SchedPolicy::RoundRobin));
+        assert_eq!(Policy::from(4).policy(), None);
+    }
+}
@@ -0,0 +1,246 @@
+use crate::{
+    data::Data,
+    modules::{
+        handle::ProcessId,
+        network::rates::NetworkRates,
+        process::{Process, State},
+    },
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// A value before and after.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Change<T> {
+    before: T,
+    after: T,
+}
+
+impl<T: PartialEq> Change<T> {
+    fn new(before: T, after: T) -> Option<Change<T>> {
+        (before != after).then_some(Change { before, after })
+    }
+}
+
+impl<T> Change<T> {
+    pub fn before(&self) -> &T {
+        &self.before
+    }
+
+    pub fn after(&self) -> &T {
+        &self.after
+    }
+}
+
+/// Differences of a process between two snapshots.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProcessChange {
+    id: ProcessId,
+    state: Option<Change<State>>,
+    user_name: Option<Change<Option<String>>>,
+    command: Option<Change<String>>,
+    priority: Option<Change<i32>>,
+    nice: Option<Change<i32>>,
+    /// Difference of the numeric statistics, only those that changed are listed.
+    deltas: BTreeMap<String, i128>,
+}
+
+impl ProcessChange {
+    fn new(before: &Process, after: &Process) -> Option<ProcessChange> {
+        let deltas: BTreeMap<String, i128> = before
+            .counters()
+            .into_iter()
+            .zip(after.counters())
+            .filter(|((_, before), (_, after))| before != after)
+            .map(|((name, before), (_, after))| (name.to_string(), after - before))
+            .collect();
+
+        let change = ProcessChange {
+            id: after.id(),
+            state: Change::new(before.state(), after.state()),
+            user_name: Change::new(
+                before.user_name().map(|s| s.to_string()),
+                after.user_name().map(|s| s.to_string()),
+            ),
+            command: Change::new(before.command().to_string(), after.command().to_string()),
+            priority: Change::new(before.priority(), after.priority()),
+            nice: Change::new(before.nice(), after.nice()),
+            deltas,
+        };
+
+        (change.has_attribute_change() || !change.deltas.is_empty()).then_some(change)
+    }
+
+    pub fn id(&self) -> &ProcessId {
+        &self.id
+    }
+
+    pub fn state(&self) -> Option<&Change<State>> {
+        self.state.as_ref()
+    }
+
+    pub fn user_name(&self) -> Option<&Change<Option<String>>> {
+        self.user_name.as_ref()
+    }
+
+    pub fn command(&self) -> Option<&Change<String>> {
+        self.command.as_ref()
+    }
+
+    pub fn priority(&self) -> Option<&Change<i32>> {
+        self.priority.as_ref()
+    }
+
+    pub fn deltas(&self) -> &BTreeMap<String, i128> {
+        &self.deltas
+    }
+
+    /// Whether the state, user, command or priority changed, as opposed to
+    /// only the counters.
+    pub fn has_attribute_change(&self) -> bool {
+        self.state.is_some()
+            || self.user_name.is_some()
+            || self.command.is_some()
+            || self.priority.is_some()
+            || self.nice.is_some()
+    }
+}
+
+/// Differences between two snapshots of the processes.
+///
+/// Processes are matched by [ProcessId], so a recycled pid shows up as
+/// one process that exited and another that started.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProcessesDiff {
+    started: Vec<Process>,
+    /// Exited processes, with their last known statistics.
+    exited: Vec<Process>,
+    changed: Vec<ProcessChange>,
+}
+
+impl ProcessesDiff {
+    pub fn new(before: &[Process], after: &[Process]) -> ProcessesDiff {
+        let before: HashMap<ProcessId, &Process> = before.iter().map(|p| (p.id(), p)).collect();
+        let after_ids: HashSet<ProcessId> = after.iter().map(|p| p.id()).collect();
+
+        let mut diff = ProcessesDiff::default();
+
+        for process in after {
+            match before.get(&process.id()) {
+                Some(previous) => diff.changed.extend(ProcessChange::new(previous, process)),
+                None => diff.started.push(process.clone()),
+            }
+        }
+
+        diff.exited = before
+            .iter()
+            .filter(|(id, _)| !after_ids.contains(id))
+            .map(|(_, process)| (*process).clone())
+            .collect();
+        diff.exited.sort_by_key(|process| process.pid());
+
+        diff
+    }
+
+    pub fn started(&self) -> &[Process] {
+        &self.started
+    }
+
+    pub fn exited(&self) -> &[Process] {
+        &self.exited
+    }
+
+    pub fn changed(&self) -> &[ProcessChange] {
+        &self.changed
+    }
+}
+
+/// Differences between two snapshots of the system.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DataDiff {
+    /// Time between the snapshots in milliseconds.
+    elapsed: u128,
+    processes: Option<ProcessesDiff>,
+    network: Option<NetworkRates>,
+}
+
+impl DataDiff {
+    pub fn new(before: &Data, after: &Data) -> DataDiff {
+        DataDiff {
+            elapsed: after.timestamp.saturating_sub(before.timestamp),
+            processes: before
+                .processes
+                .as_ref()
+                .zip(after.processes.as_ref())
+                .map(|(before, after)| ProcessesDiff::new(before, after)),
+            network: before
+                .network
+                .as_ref()
+                .zip(after.network.as_ref())
+                .and_then(|(before, after)| NetworkRates::new(before, after)),
+        }
+    }
+
+    pub fn elapsed(&self) -> u128 {
+        self.elapsed
+    }
+
+    pub fn processes(&self) -> Option<&ProcessesDiff> {
+        self.processes.as_ref()
+    }
+
+    pub fn network(&self) -> Option<&NetworkRates> {
+        self.network.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NGINX: &str = "4242 (nginx) S 4220 4242 4242 0 -1 4194560 1210 0 3 0 12 8 0 0 20 0 1 0 982311 11689984 1530 18446744073709551615 94019321487360 94019322502093 140726480367408 0 0 0 0 1073745920 402745863 0 0 0 17 0 0 0 0 0 0 94019322798512 94019322861936 94019348111360 140726480372510 140726480372550 140726480372550 140726480375781 0\n";
+
+    #[test]
+    fn test_diff() {
+        let stat = |s: &str| Process::new(s, Some("root".into())).unwrap();
+
+        let before = vec![
+            stat(NGINX),
+            stat(&NGINX.replace("4242 (nginx)", "4243 (nginx)")),
+            stat(&NGINX.replace("4242 (nginx)", "4244 (nginx)")),
+        ];
+        let after = vec![
+            stat(
+                &NGINX
+                    .replace("(nginx) S", "(nginx) R")
+                    .replace(" 12 8 ", " 20 8 "),
+            ),
+            stat(&NGINX.replace("4242 (nginx)", "4243 (nginx)")),
+            // pid 4244 was recycled
+            stat(
+                &NGINX
+                    .replace("4242 (nginx)", "4244 (bash)")
+                    .replace("982311", "990000"),
+            ),
+        ];
+
+        let diff = ProcessesDiff::new(&before, &after);
+
+        assert_eq!(diff.started.len(), 1);
+        assert_eq!(diff.started[0].command(), "bash");
+        assert_eq!(diff.exited.len(), 1);
+        assert_eq!(diff.exited[0].pid(), 4244);
+
+        assert_eq!(diff.changed.len(), 1);
+        let change = &diff.changed[0];
+        assert_eq!(change.id.pid(), 4242);
+        assert_eq!(
+            change.state,
+            Some(Change {
+                before: State::S,
+                after: State::R
+            })
+        );
+        assert_eq!(change.deltas, BTreeMap::from([("utime".to_string(), 8)]));
+    }
+}
@@ -0,0 +1,495 @@
+use crate::{
+    modules::{
+        decode::Signal,
+        diff::ProcessesDiff,
+        misc::Misc,
+        netlink::{self, u32_at},
+        process::{self, ParseOptions, Process, Processes},
+    },
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    io,
+    os::fd::OwnedFd,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Index and value of the proc connector, defined in `include/uapi/linux/connector.h`.
+const CN_IDX_PROC: u32 = 1;
+const CN_VAL_PROC: u32 = 1;
+
+/// Operation asking the kernel to start multicasting process events.
+const PROC_CN_MCAST_LISTEN: u32 = 1;
+
+/// Event types of `struct proc_event`, defined in `include/uapi/linux/cn_proc.h`.
+/// `PROC_EVENT_NONE` acknowledges a `PROC_CN_MCAST_LISTEN`.
+const PROC_EVENT_NONE: u32 = 0x0000_0000;
+const PROC_EVENT_FORK: u32 = 0x0000_0001;
+const PROC_EVENT_EXEC: u32 = 0x0000_0002;
+const PROC_EVENT_UID: u32 = 0x0000_0004;
+const PROC_EVENT_COMM: u32 = 0x0000_0200;
+const PROC_EVENT_EXIT: u32 = 0x8000_0000;
+
+/// Size of `struct cn_msg`, without its payload.
+const CN_MSG_LEN: usize = 20;
+/// Time to wait for the kernel to acknowledge the subscription.
+const ACK_TIMEOUT: Duration = Duration::from_secs(1);
+/// Offset of the event data in `struct proc_event`, after `what`, `cpu` and `timestamp_ns`.
+const EVENT_DATA: usize = 16;
+
+/// A change in the lifecycle of a process.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ProcessEvent {
+    /// A process or thread was created.
+    Fork {
+        parent_pid: u32,
+        parent_tgid: u32,
+        child_pid: u32,
+        child_tgid: u32,
+    },
+    /// A process executed a new program.
+    Exec {
+        pid: u32,
+        tgid: u32,
+        /// The process read right after the event, `None` if it already exited.
+        process: Option<Box<Process>>,
+    },
+    /// A process or thread exited.
+    Exit {
+        pid: u32,
+        tgid: u32,
+        /// Exit code, if the process exited normally and it is known.
+        code: Option<i32>,
+        /// Signal that killed the process, if any.
+        signal: Option<Signal>,
+    },
+    /// The real or effective user of a process changed.
+    Uid {
+        pid: u32,
+        tgid: u32,
+        ruid: u32,
+        euid: u32,
+    },
+    /// A process changed its command name, e.g. with `prctl(PR_SET_NAME)`.
+    Comm {
+        pid: u32,
+        tgid: u32,
+        command: String,
+    },
+}
+
+enum Source {
+    /// Netlink socket subscribed to the kernel proc connector.
+    Connector(OwnedFd),
+    /// Snapshots of `/proc` compared at a fixed interval.
+    Polling {
+        previous: Processes,
+        interval: Duration,
+    },
+}
+
+/// Stream of process events.
+///
+/// Events come from the kernel proc connector, which requires `CAP_NET_ADMIN`.
+/// Without it, [ProcessEvents::new] falls back to comparing snapshots of
+/// `/proc`, in which case short-lived processes are missed, forks are not
+/// distinguished from execs, exit codes are unknown and uid changes are not
+/// reported.
+///
+/// Iterating blocks until the next event.
+pub struct ProcessEvents {
+    source: Source,
+    pending: VecDeque<ProcessEvent>,
+    /// Read once rather than for every exec event.
+    misc: Option<Misc>,
+    options: ParseOptions,
+}
+
+impl ProcessEvents {
+    /// Subscribe to the proc connector, or poll `/proc` every `interval` if
+    /// that is not possible.
+    pub fn new(interval: Duration) -> Result<ProcessEvents, DataError> {
+        ProcessEvents::connector().or_else(|e| {
+            log::debug!("proc connector unavailable ({:?}), polling /proc", e);
+            ProcessEvents::polling(interval)
+        })
+    }
+
+    /// Subscribe to the kernel proc connector.
+    pub fn connector() -> Result<ProcessEvents, DataError> {
+        let socket = subscribe().map_err(|e| match e.raw_os_error() {
+            Some(libc::EPERM) | Some(libc::EACCES) => DataError::PermissionDenied,
+            _ => DataError::FileNotFound,
+        })?;
+
+        Ok(ProcessEvents {
+            source: Source::Connector(socket),
+            pending: VecDeque::new(),
+            misc: Misc::parse().ok(),
+            options: ParseOptions::default(),
+        })
+    }
+
+    /// Compare snapshots of `/proc` taken every `interval`.
+    pub fn polling(interval: Duration) -> Result<ProcessEvents, DataError> {
+        let options = ParseOptions::default();
+
+        Ok(ProcessEvents {
+            source: Source::Polling {
+                previous: process::parse_with(&options)?,
+                interval,
+            },
+            pending: VecDeque::new(),
+            misc: Misc::parse().ok(),
+            options,
+        })
+    }
+
+    /// Whether events come from the kernel, as opposed to polling.
+    pub fn is_realtime(&self) -> bool {
+        matches!(self.source, Source::Connector(_))
+    }
+
+    /// Wait for the next batch of events and queue them.
+    fn fill(&mut self) -> Result<(), DataError> {
+        match &mut self.source {
+            Source::Connector(socket) => {
+                let mut buffer = [0u8; 4096];
+
+                let len = match netlink::recv(socket, &mut buffer) {
+                    Ok(len) => len,
+                    // Events were dropped because we were too slow
+                    Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => return Ok(()),
+                    Err(_) => return Err(DataError::FileNotFound),
+                };
+
+                let (options, misc) = (&self.options, self.misc.as_ref());
+                self.pending.extend(
+                    parse_messages(&buffer[..len]).map(|event| enrich(event, options, misc)),
+                );
+            }
+            Source::Polling { previous, interval } => {
+                thread::sleep(*interval);
+
+                let current = process::parse_with(&self.options)?;
+                self.pending
+                    .extend(from_diff(&ProcessesDiff::new(previous, &current)));
+                *previous = current;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for ProcessEvents {
+    type Item = Result<ProcessEvent, DataError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if let Err(e) = self.fill() {
+                return Some(Err(e));
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Open a netlink socket and ask the kernel for process events.
+///
+/// Binding the socket succeeds without privileges, only the acknowledgement
+/// of the request tells whether events will be sent. The kernel answers
+/// with `EPERM` without `CAP_NET_ADMIN`, and does not answer at all outside
+/// of the initial user and pid namespaces, which is reported as `EAGAIN`.
+fn subscribe() -> io::Result<OwnedFd> {
+    let socket = netlink::socket(libc::NETLINK_CONNECTOR, CN_IDX_PROC)?;
+
+    // Acknowledgements are multicast, they are told apart by the ack number
+    let ack = std::process::id();
+    let payload = PROC_CN_MCAST_LISTEN.to_ne_bytes();
+    netlink::send(
+        &socket,
+        &netlink::message(libc::NLMSG_DONE as u16, 0, &cn_msg(ack, &payload)),
+    )?;
+
+    netlink::set_timeout(&socket, Some(ACK_TIMEOUT))?;
+    let deadline = Instant::now() + ACK_TIMEOUT;
+    let mut buffer = [0u8; 4096];
+
+    // Events of other listeners may arrive before the acknowledgement
+    while Instant::now() < deadline {
+        let len = netlink::recv(&socket, &mut buffer)?;
+
+        for (_, message) in netlink::messages(&buffer[..len]) {
+            match parse_ack(message, ack) {
+                Some(0) => {
+                    netlink::set_timeout(&socket, None)?;
+                    return Ok(socket);
+                }
+                Some(err) => return Err(io::Error::from_raw_os_error(err as i32)),
+                None => {}
+            }
+        }
+    }
+
+    Err(io::Error::from_raw_os_error(libc::EAGAIN))
+}
+
+/// Build a `struct cn_msg` addressed to the proc connector.
+fn cn_msg(ack: u32, payload: &[u8]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(CN_MSG_LEN + payload.len());
+    message.extend(CN_IDX_PROC.to_ne_bytes());
+    message.extend(CN_VAL_PROC.to_ne_bytes());
+    // seq
+    message.extend([0; 4]);
+    message.extend(ack.to_ne_bytes());
+    message.extend((payload.len() as u16).to_ne_bytes());
+    // flags
+    message.extend([0; 2]);
+    message.extend(payload);
+
+    message
+}
+
+/// Split a netlink datagram into its messages and decode the process events.
+fn parse_messages(data: &[u8]) -> impl Iterator<Item = ProcessEvent> + '_ {
+    netlink::messages(data).filter_map(|(_, payload)| parse_event(payload))
+}
+
+/// Decode the error of the acknowledgement of the request sent with `ack`,
+/// `None` if the message is anything else.
+fn parse_ack(message: &[u8], ack: u32) -> Option<u32> {
+    if u32_at(message, 0)? != CN_IDX_PROC
+        || u32_at(message, 4)? != CN_VAL_PROC
+        // The kernel answers with the next ack number
+        || u32_at(message, 12)? != ack.wrapping_add(1)
+    {
+        return None;
+    }
+
+    let event = message.get(CN_MSG_LEN..)?;
+    if u32_at(event, 0)? != PROC_EVENT_NONE {
+        return None;
+    }
+
+    u32_at(event, EVENT_DATA)
+}
+
+/// Decode a `struct cn_msg` carrying a `struct proc_event`.
+fn parse_event(message: &[u8]) -> Option<ProcessEvent> {
+    if u32_at(message, 0)? != CN_IDX_PROC || u32_at(message, 4)? != CN_VAL_PROC {
+        return None;
+    }
+
+    let event = message.get(CN_MSG_LEN..)?;
+    let what = u32_at(event, 0)?;
+    let field = |n: usize| u32_at(event, EVENT_DATA + 4 * n);
+
+    Some(match what {
+        PROC_EVENT_FORK => ProcessEvent::Fork {
+            parent_pid: field(0)?,
+            parent_tgid: field(1)?,
+            child_pid: field(2)?,
+            child_tgid: field(3)?,
+        },
+        PROC_EVENT_EXEC => ProcessEvent::Exec {
+            pid: field(0)?,
+            tgid: field(1)?,
+            process: None,
+        },
+        PROC_EVENT_EXIT => {
+            // The exit code is a wait status, refer to `waitpid(2)`.
+            let status = field(2)? as libc::c_int;
+
+            ProcessEvent::Exit {
+                pid: field(0)?,
+                tgid: field(1)?,
+                code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+                signal: libc::WIFSIGNALED(status)
+                    .then(|| Signal::from_number(libc::WTERMSIG(status) as u8))
+                    .flatten(),
+            }
+        }
+        PROC_EVENT_UID => ProcessEvent::Uid {
+            pid: field(0)?,
+            tgid: field(1)?,
+            ruid: field(2)?,
+            euid: field(3)?,
+        },
+        PROC_EVENT_COMM => {
+            let comm = event.get(EVENT_DATA + 8..EVENT_DATA + 24)?;
+            let end = comm.iter().position(|&c| c == 0).unwrap_or(comm.len());
+
+            ProcessEvent::Comm {
+                pid: field(0)?,
+                tgid: field(1)?,
+                command: String::from_utf8_lossy(&comm[..end]).into_owned(),
+            }
+        }
+        _ => return None,
+    })
+}
+
+/// Read the process that just executed a new program.
+fn enrich(event: ProcessEvent, options: &ParseOptions, misc: Option<&Misc>) -> ProcessEvent {
+    match event {
+        ProcessEvent::Exec { pid, tgid, .. } => ProcessEvent::Exec {
+            pid,
+            tgid,
+            process: Process::from_pid_with(tgid, options, misc)
+                .ok()
+                .map(Box::new),
+        },
+        event => event,
+    }
+}
+
+/// Translate the differences between two snapshots into events.
+fn from_diff(diff: &ProcessesDiff) -> Vec<ProcessEvent> {
+    let started = diff.started().iter().map(|process| ProcessEvent::Exec {
+        pid: process.pid(),
+        tgid: process.pid(),
+        process: Some(Box::new(process.clone())),
+    });
+
+    let renamed = diff.changed().iter().filter_map(|change| {
+        change.command().map(|command| ProcessEvent::Comm {
+            pid: change.id().pid(),
+            tgid: change.id().pid(),
+            command: command.after().clone(),
+        })
+    });
+
+    let exited = diff.exited().iter().map(|process| ProcessEvent::Exit {
+        pid: process.pid(),
+        tgid: process.pid(),
+        code: None,
+        signal: None,
+    });
+
+    started.chain(renamed).chain(exited).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn message_with_ack(ack: u32, what: u32, data: &[u32]) -> Vec<u8> {
+        let mut event: Vec<u8> = vec![];
+        event.extend(what.to_ne_bytes());
+        event.extend(0u32.to_ne_bytes());
+        event.extend(0u64.to_ne_bytes());
+        data.iter().for_each(|n| event.extend(n.to_ne_bytes()));
+
+        netlink::message(libc::NLMSG_DONE as u16, 0, &cn_msg(ack, &event))
+    }
+
+    fn message(what: u32, data: &[u32]) -> Vec<u8> {
+        message_with_ack(0, what, data)
+    }
+
+    #[test]
+    fn test_parse_ack() {
+        let mut data = message(PROC_EVENT_FORK, &[1, 1, 4242, 4242]);
+        // Acknowledgement of another listener
+        data.extend(message_with_ack(8, PROC_EVENT_NONE, &[0]));
+        data.extend(message_with_ack(43, PROC_EVENT_NONE, &[libc::EPERM as u32]));
+
+        let acks: Vec<Option<u32>> = netlink::messages(&data)
+            .map(|(_, message)| parse_ack(message, 42))
+            .collect();
+        assert_eq!(acks, vec![None, None, Some(libc::EPERM as u32)]);
+
+        let ok = message_with_ack(43, PROC_EVENT_NONE, &[0]);
+        let (_, message) = netlink::messages(&ok).next().unwrap();
+        assert_eq!(parse_ack(message, 42), Some(0));
+
+        // Acknowledgements are not events
+        assert_eq!(parse_messages(&data).count(), 1);
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        let mut data = message(PROC_EVENT_FORK, &[1, 1, 4242, 4242]);
+        // exit status of SIGKILL
+        data.extend(message(PROC_EVENT_EXIT, &[4242, 4242, 9, 17, 1, 1]));
+        data.extend(message(PROC_EVENT_EXIT, &[4243, 4243, 3 << 8, 17, 1, 1]));
+        let comm = u32::from_ne_bytes(*b"bash");
+        data.extend(message(PROC_EVENT_COMM, &[4244, 4244, comm, 0, 0, 0]));
+
+        let events: Vec<ProcessEvent> = parse_messages(&data).collect();
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0],
+            ProcessEvent::Fork {
+                parent_pid: 1,
+                child_pid: 4242,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[1],
+            ProcessEvent::Exit {
+                pid: 4242,
+                code: None,
+                signal: Some(Signal::SIGKILL),
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[2],
+            ProcessEvent::Exit {
+                code: Some(3),
+                signal: None,
+                ..
+            }
+        ));
+        assert!(matches!(&events[3], ProcessEvent::Comm { command, .. } if command == "bash"));
+    }
+
+    #[test]
+    fn test_enrich() {
+        let misc = Misc::parse().unwrap();
+        let exec = ProcessEvent::Exec {
+            pid: 4242,
+            tgid: 4242,
+            process: None,
+        };
+
+        let event = enrich(exec, &ParseOptions::minimal(), Some(&misc));
+        assert!(
+            matches!(event, ProcessEvent::Exec { process: Some(process), .. } if process.command() == "nginx")
+        );
+    }
+
+    #[test]
+    fn test_from_diff() {
+        let after = process::parse_with(&ParseOptions::minimal()).unwrap();
+        let before: Processes = after.iter().filter(|p| p.pid() != 4242).cloned().collect();
+
+        let events = from_diff(&ProcessesDiff::new(&before, &after));
+        assert!(matches!(
+            events[..],
+            [ProcessEvent::Exec {
+                pid: 4242,
+                process: Some(_),
+                ..
+            }]
+        ));
+
+        let events = from_diff(&ProcessesDiff::new(&after, &before));
+        assert!(matches!(
+            events[..],
+            [ProcessEvent::Exit {
+                pid: 4242,
+                code: None,
+                ..
+            }]
+        ));
+    }
+}
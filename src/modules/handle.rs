@@ -0,0 +1,345 @@
+use crate::{
+    modules::{
+        decode::Signal,
+        limits::ProcessLimits,
+        maps::MemoryMaps,
+        process::{Process, State},
+    },
+    parser::DataError,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    hash::{Hash, Hasher},
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(test)]
+const BOOT_ID: &str = "./mock/boot_id";
+#[cfg(not(test))]
+const BOOT_ID: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Identity of a process.
+///
+/// Pids are recycled by the kernel, but no two processes with the same pid
+/// can have started at the same time since boot. Adding the boot id makes
+/// the identity unique across reboots.
+///
+/// Ids compare by pid and start time only, so that an id with a boot id
+/// equals the same id without one. The boot id is checked by
+/// [ProcessHandle::validate].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProcessId {
+    pid: u32,
+    /// Time the process started after system boot, in clock ticks.
+    starttime: u64,
+    boot_id: Option<String>,
+}
+
+impl ProcessId {
+    pub fn new(pid: u32, starttime: u64) -> ProcessId {
+        ProcessId {
+            pid,
+            starttime,
+            boot_id: None,
+        }
+    }
+
+    /// Add the id of the current boot to the identity.
+    pub fn with_boot_id(mut self) -> ProcessId {
+        self.boot_id = boot_id();
+        self
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn starttime(&self) -> u64 {
+        self.starttime
+    }
+
+    pub fn boot_id(&self) -> Option<&str> {
+        self.boot_id.as_deref()
+    }
+
+    /// Whether `process` is the process identified by this id.
+    pub fn matches(&self, process: &Process) -> bool {
+        self.pid == process.pid() && self.starttime == process.starttime()
+    }
+}
+
+impl PartialEq for ProcessId {
+    fn eq(&self, other: &Self) -> bool {
+        (self.pid, self.starttime) == (other.pid, other.starttime)
+    }
+}
+
+impl Eq for ProcessId {}
+
+impl Hash for ProcessId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.pid, self.starttime).hash(state);
+    }
+}
+
+/// Read the random id generated by the kernel at boot.
+fn boot_id() -> Option<String> {
+    fs::read_to_string(BOOT_ID)
+        .ok()
+        .map(|id| id.trim().to_string())
+}
+
+/// Interval at which `/proc` is checked when waiting without a pidfd.
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A reference to a process that is safe against pid reuse.
+///
+/// The identity of the process is checked before every read or action,
+/// which fails with [DataError::ProcessReplaced] if the pid was recycled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessHandle {
+    id: ProcessId,
+}
+
+impl ProcessHandle {
+    pub fn new(id: ProcessId) -> ProcessHandle {
+        ProcessHandle { id }
+    }
+
+    pub fn id(&self) -> &ProcessId {
+        &self.id
+    }
+
+    /// Check that the pid still belongs to the process.
+    pub fn validate(&self) -> Result<(), DataError> {
+        self.read().map(|_| ())
+    }
+
+    /// Read the current state of the process.
+    pub fn read(&self) -> Result<Process, DataError> {
+        if self.id.boot_id.is_some() && self.id.boot_id != boot_id() {
+            return Err(DataError::ProcessExited);
+        }
+
+        let process = Process::read_stat(self.id.pid)?;
+
+        if self.id.matches(&process) {
+            Ok(process)
+        } else {
+            Err(DataError::ProcessReplaced)
+        }
+    }
+
+    pub fn limits(&self) -> Result<ProcessLimits, DataError> {
+        self.validated(ProcessLimits::from_pid(self.id.pid))
+    }
+
+    pub fn maps(&self) -> Result<MemoryMaps, DataError> {
+        self.validated(MemoryMaps::from_pid(self.id.pid))
+    }
+
+    /// Send a signal to the process.
+    pub fn signal(&self, signal: Signal) -> Result<(), DataError> {
+        let sig = signal.number().ok_or(DataError::InvalidSignal)? as libc::c_int;
+
+        // Holding a pidfd pins the process, so it cannot be replaced
+        // between the validation and the signal.
+        let pidfd = pidfd_open(self.id.pid);
+        self.validate()?;
+
+        // SAFETY: the fd is valid for the duration of the call, a null siginfo is allowed.
+        let res = match &pidfd {
+            Ok(fd) => unsafe {
+                libc::syscall(
+                    libc::SYS_pidfd_send_signal,
+                    fd.as_raw_fd(),
+                    sig,
+                    std::ptr::null::<libc::siginfo_t>(),
+                    0,
+                ) as libc::c_int
+            },
+            Err(_) => unsafe { libc::kill(self.id.pid as libc::pid_t, sig) },
+        };
+
+        if res == 0 {
+            return Ok(());
+        }
+
+        match io::Error::last_os_error().raw_os_error() {
+            Some(libc::ESRCH) => Err(DataError::ProcessExited),
+            Some(libc::EINVAL) => Err(DataError::InvalidSignal),
+            _ => Err(DataError::PermissionDenied),
+        }
+    }
+
+    /// Obtain a pidfd of the process, which becomes readable once it exits.
+    ///
+    /// This allows waiting asynchronously by registering the file descriptor
+    /// with an event loop, see [ProcessHandle::wait] for a blocking wait.
+    pub fn pidfd(&self) -> Result<OwnedFd, DataError> {
+        let pidfd = pidfd_open(self.id.pid).map_err(|e| match e.raw_os_error() {
+            Some(libc::ESRCH) => DataError::ProcessExited,
+            Some(libc::EPERM) => DataError::PermissionDenied,
+            _ => DataError::FileNotFound,
+        })?;
+        self.validate()?;
+
+        Ok(pidfd)
+    }
+
+    /// Block until the process exits, or `timeout` elapses.
+    ///
+    /// Unlike `waitpid(2)`, this works for any process, not only children.
+    /// A recycled pid is never mistaken for the original process.
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<WaitStatus, DataError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        match self.pidfd() {
+            Ok(pidfd) => wait_pidfd(&pidfd, deadline),
+            Err(DataError::ProcessExited) | Err(DataError::ProcessReplaced) => {
+                Ok(WaitStatus::Exited)
+            }
+            // pidfd_open is not available before Linux 5.3
+            Err(_) => self.wait_proc(deadline),
+        }
+    }
+
+    /// Wait by checking `/proc/[pid]/stat` periodically.
+    fn wait_proc(&self, deadline: Option<Instant>) -> Result<WaitStatus, DataError> {
+        loop {
+            match self.read() {
+                // A zombie has exited, only its parent did not reap it yet
+                Ok(process) if process.state() == State::Z => return Ok(WaitStatus::Exited),
+                Ok(_) => {}
+                Err(DataError::ProcessExited) | Err(DataError::ProcessReplaced) => {
+                    return Ok(WaitStatus::Exited)
+                }
+                Err(e) => return Err(e),
+            }
+
+            let interval = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => remaining.min(WAIT_INTERVAL),
+                    _ => return Ok(WaitStatus::TimedOut),
+                },
+                None => WAIT_INTERVAL,
+            };
+            thread::sleep(interval);
+        }
+    }
+
+    /// Check the identity after reading a file of the process, so that data
+    /// of a process that replaced the original is never returned.
+    fn validated<T>(&self, data: Result<T, DataError>) -> Result<T, DataError> {
+        self.validate()?;
+
+        data
+    }
+}
+
+/// Outcome of [ProcessHandle::wait].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitStatus {
+    Exited,
+    TimedOut,
+}
+
+/// Wait until a pidfd becomes readable.
+fn wait_pidfd(pidfd: &OwnedFd, deadline: Option<Instant>) -> Result<WaitStatus, DataError> {
+    loop {
+        let timeout = match deadline {
+            Some(deadline) => deadline
+                .saturating_duration_since(Instant::now())
+                .as_millis()
+                .min(libc::c_int::MAX as u128) as libc::c_int,
+            None => -1,
+        };
+
+        let mut fds = libc::pollfd {
+            fd: pidfd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        // SAFETY: fds points to a single valid pollfd.
+        match unsafe { libc::poll(&mut fds, 1, timeout) } {
+            0 => return Ok(WaitStatus::TimedOut),
+            res if res > 0 => return Ok(WaitStatus::Exited),
+            _ if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => continue,
+            _ => return Err(DataError::FileNotFound),
+        }
+    }
+}
+
+/// Obtain a file descriptor referring to a process, refer to `pidfd_open(2)`.
+pub(crate) fn pidfd_open(pid: u32) -> io::Result<OwnedFd> {
+    // SAFETY: pidfd_open has no memory safety preconditions.
+    let fd = unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) };
+
+    if fd < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        // SAFETY: the fd was just created and is not owned by anything else.
+        Ok(unsafe { OwnedFd::from_raw_fd(fd as libc::c_int) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let handle = ProcessHandle::new(ProcessId::new(1, 12).with_boot_id());
+        assert_eq!(
+            handle.id().boot_id(),
+            Some("5e2b1c4a-9d7f-4b83-a1e6-0f3c2d8b7a91")
+        );
+        assert!(handle.validate().is_ok());
+        assert!(handle.maps().is_ok());
+        assert!(matches!(
+            handle.signal(Signal::SIGRT(200)),
+            Err(DataError::InvalidSignal)
+        ));
+
+        let replaced = ProcessHandle::new(ProcessId::new(1, 13));
+        assert!(matches!(replaced.read(), Err(DataError::ProcessReplaced)));
+        assert!(matches!(replaced.limits(), Err(DataError::ProcessReplaced)));
+
+        let exited = ProcessHandle::new(ProcessId::new(999999, 12));
+        assert!(matches!(exited.validate(), Err(DataError::ProcessExited)));
+    }
+
+    #[test]
+    fn test_id() {
+        let id = ProcessId::new(1, 12);
+        let with_boot_id = ProcessId::new(1, 12).with_boot_id();
+        assert!(with_boot_id.boot_id().is_some());
+        assert_eq!(id, with_boot_id);
+
+        let ids: std::collections::HashSet<ProcessId> = [id, with_boot_id].into();
+        assert_eq!(ids.len(), 1);
+        assert_ne!(ProcessId::new(1, 12), ProcessId::new(1, 13));
+    }
+
+    #[test]
+    fn test_wait() {
+        let timeout = Some(Duration::from_millis(10));
+
+        let init = ProcessHandle::new(ProcessId::new(1, 12));
+        assert_eq!(init.wait(timeout).unwrap(), WaitStatus::TimedOut);
+        let deadline = Some(Instant::now() + Duration::from_millis(10));
+        assert_eq!(init.wait_proc(deadline).unwrap(), WaitStatus::TimedOut);
+
+        let replaced = ProcessHandle::new(ProcessId::new(1, 13));
+        assert_eq!(replaced.wait(None).unwrap(), WaitStatus::Exited);
+        assert_eq!(replaced.wait_proc(None).unwrap(), WaitStatus::Exited);
+
+        let exited = ProcessHandle::new(ProcessId::new(999999, 12));
+        assert_eq!(exited.wait(None).unwrap(), WaitStatus::Exited);
+    }
+}
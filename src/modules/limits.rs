@@ -0,0 +1,367 @@
+use crate::{
+    helper::page_size,
+    modules::process::{Process, PROC},
+    parser::DataError,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, os::unix::fs::MetadataExt};
+
+/// Width of the "Limit" column of `/proc/[pid]/limits`.
+const NAME_WIDTH: usize = 26;
+
+/// A resource limit, `None` stands for `unlimited`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Limit {
+    soft: Option<u64>,
+    hard: Option<u64>,
+    unit: Option<String>,
+}
+
+impl Limit {
+    pub fn soft(&self) -> Option<u64> {
+        self.soft
+    }
+
+    pub fn hard(&self) -> Option<u64> {
+        self.hard
+    }
+}
+
+/// Rust representation of `/proc/[pid]/limits`, refer to `getrlimit(2)` for details.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessLimits {
+    /// RLIMIT_CPU
+    cpu_time: Limit,
+    /// RLIMIT_FSIZE
+    file_size: Limit,
+    /// RLIMIT_DATA
+    data_size: Limit,
+    /// RLIMIT_STACK
+    stack_size: Limit,
+    /// RLIMIT_CORE
+    core_file_size: Limit,
+    /// RLIMIT_RSS
+    resident_set: Limit,
+    /// RLIMIT_NPROC
+    processes: Limit,
+    /// RLIMIT_NOFILE
+    open_files: Limit,
+    /// RLIMIT_MEMLOCK
+    locked_memory: Limit,
+    /// RLIMIT_AS
+    address_space: Limit,
+    /// RLIMIT_LOCKS
+    file_locks: Limit,
+    /// RLIMIT_SIGPENDING
+    pending_signals: Limit,
+    /// RLIMIT_MSGQUEUE
+    msgqueue_size: Limit,
+    /// RLIMIT_NICE
+    nice_priority: Limit,
+    /// RLIMIT_RTPRIO
+    realtime_priority: Limit,
+    /// RLIMIT_RTTIME
+    realtime_timeout: Limit,
+}
+
+/// Resources whose usage can be compared against a limit.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// Open file descriptors against RLIMIT_NOFILE.
+    OpenFiles,
+    /// Threads of the user against RLIMIT_NPROC.
+    Threads,
+    /// Virtual memory size against RLIMIT_AS.
+    AddressSpace,
+    /// Resident set size against RLIMIT_DATA.
+    Data,
+    /// Resident set size against `rsslim` of `/proc/[pid]/stat`.
+    ResidentSet,
+}
+
+/// Usage of a resource relative to its soft limit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LimitUsage {
+    resource: Resource,
+    usage: u64,
+    limit: u64,
+    percent: f32,
+}
+
+impl LimitUsage {
+    fn new(resource: Resource, usage: u64, limit: Option<u64>) -> Option<LimitUsage> {
+        let limit = limit.filter(|limit| *limit != 0 && *limit != u64::MAX)?;
+
+        Some(LimitUsage {
+            resource,
+            usage,
+            limit,
+            percent: usage as f32 / limit as f32 * 100.0,
+        })
+    }
+
+    pub fn resource(&self) -> Resource {
+        self.resource
+    }
+
+    pub fn percent(&self) -> f32 {
+        self.percent
+    }
+}
+
+/// Limits a process is getting close to.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LimitReport {
+    pid: u32,
+    command: String,
+    usages: Vec<LimitUsage>,
+}
+
+impl LimitReport {
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn usages(&self) -> &[LimitUsage] {
+        &self.usages
+    }
+}
+
+impl ProcessLimits {
+    pub fn from_pid(pid: u32) -> Result<ProcessLimits, DataError> {
+        let content = fs::read_to_string(format!("{}{}/limits", PROC, pid))
+            .map_err(|_| DataError::FileNotFound)?;
+
+        ProcessLimits::parse_table(&content)
+    }
+
+    fn parse_table(content: &str) -> Result<ProcessLimits, DataError> {
+        let mut limits = ProcessLimits::default();
+
+        // Skip the header
+        for line in content.lines().skip(1) {
+            if line.len() < NAME_WIDTH {
+                return Err(DataError::Parsing);
+            }
+
+            let (name, values) = line.split_at(NAME_WIDTH);
+            let mut values = values.split_whitespace();
+
+            let mut value = || match values.next() {
+                Some("unlimited") => Ok(None),
+                Some(val) => val.parse().map(Some).map_err(|_| DataError::Parsing),
+                None => Err(DataError::Parsing),
+            };
+
+            let limit = Limit {
+                soft: value()?,
+                hard: value()?,
+                unit: values.next().map(|s| s.to_string()),
+            };
+
+            let field = match name.trim() {
+                "Max cpu time" => &mut limits.cpu_time,
+                "Max file size" => &mut limits.file_size,
+                "Max data size" => &mut limits.data_size,
+                "Max stack size" => &mut limits.stack_size,
+                "Max core file size" => &mut limits.core_file_size,
+                "Max resident set" => &mut limits.resident_set,
+                "Max processes" => &mut limits.processes,
+                "Max open files" => &mut limits.open_files,
+                "Max locked memory" => &mut limits.locked_memory,
+                "Max address space" => &mut limits.address_space,
+                "Max file locks" => &mut limits.file_locks,
+                "Max pending signals" => &mut limits.pending_signals,
+                "Max msgqueue size" => &mut limits.msgqueue_size,
+                "Max nice priority" => &mut limits.nice_priority,
+                "Max realtime priority" => &mut limits.realtime_priority,
+                "Max realtime timeout" => &mut limits.realtime_timeout,
+                _ => continue,
+            };
+
+            *field = limit;
+        }
+
+        Ok(limits)
+    }
+
+    pub fn open_files(&self) -> &Limit {
+        &self.open_files
+    }
+
+    pub fn processes(&self) -> &Limit {
+        &self.processes
+    }
+
+    pub fn address_space(&self) -> &Limit {
+        &self.address_space
+    }
+
+    pub fn data_size(&self) -> &Limit {
+        &self.data_size
+    }
+
+    /// Compare the current usage of a process against its soft limits.
+    ///
+    /// `user_threads` is the number of threads of the owner of the process,
+    /// as RLIMIT_NPROC applies to all of them. If `None`, only the threads
+    /// of the process itself are counted.
+    pub fn usage(&self, process: &Process, user_threads: Option<u64>) -> Vec<LimitUsage> {
+        let rss = process.rss().max(0) as u64 * page_size();
+        let threads = user_threads.unwrap_or(process.num_threads().max(0) as u64);
+
+        [
+            open_fds(process.pid())
+                .and_then(|fds| LimitUsage::new(Resource::OpenFiles, fds, self.open_files.soft)),
+            LimitUsage::new(Resource::Threads, threads, self.processes.soft),
+            LimitUsage::new(
+                Resource::AddressSpace,
+                process.vsize(),
+                self.address_space.soft,
+            ),
+            LimitUsage::new(Resource::Data, rss, self.data_size.soft),
+            LimitUsage::new(Resource::ResidentSet, rss, Some(process.rsslim())),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// Number of file descriptors opened by a process.
+fn open_fds(pid: u32) -> Option<u64> {
+    fs::read_dir(format!("{}{}/fd", PROC, pid))
+        .ok()
+        .map(|dir| dir.count() as u64)
+}
+
+/// Real user ID of a process, which RLIMIT_NPROC is accounted to.
+///
+/// Read from `/proc/[pid]/status`, or the owner of `/proc/[pid]` if the file
+/// is not readable.
+fn real_uid(pid: u32) -> Option<u32> {
+    let status = fs::read_to_string(format!("{}{}/status", PROC, pid)).ok();
+
+    status
+        .as_deref()
+        .and_then(|status| {
+            status
+                .lines()
+                .find_map(|line| line.strip_prefix("Uid:"))?
+                .split_whitespace()
+                .next()?
+                .parse()
+                .ok()
+        })
+        .or_else(|| {
+            fs::metadata(format!("{}{}", PROC, pid))
+                .ok()
+                .map(|metadata| metadata.uid())
+        })
+}
+
+/// Threads per real user ID, given the user ID of every process.
+fn threads_by_uid(processes: &[Process], uids: &HashMap<u32, u32>) -> HashMap<u32, u64> {
+    let mut threads: HashMap<u32, u64> = HashMap::new();
+    for process in processes {
+        if let Some(uid) = uids.get(&process.pid()) {
+            *threads.entry(*uid).or_default() += process.num_threads().max(0) as u64;
+        }
+    }
+
+    threads
+}
+
+/// Report the processes using more than `threshold` percent of any of their limits.
+pub fn report(processes: &[Process], threshold: f32) -> Vec<LimitReport> {
+    let uids: HashMap<u32, u32> = processes
+        .iter()
+        .filter_map(|process| Some((process.pid(), real_uid(process.pid())?)))
+        .collect();
+    let user_threads = threads_by_uid(processes, &uids);
+
+    processes
+        .iter()
+        .filter_map(|process| {
+            let limits = ProcessLimits::from_pid(process.pid()).ok()?;
+            let threads = uids
+                .get(&process.pid())
+                .and_then(|uid| user_threads.get(uid))
+                .copied();
+
+            let usages: Vec<LimitUsage> = limits
+                .usage(process, threads)
+                .into_iter()
+                .filter(|usage| usage.percent >= threshold)
+                .collect();
+
+            (!usages.is_empty()).then(|| LimitReport {
+                pid: process.pid(),
+                command: process.command().to_string(),
+                usages,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        modules::process::{self, ParseOptions, Processes},
+        parser::Parser,
+    };
+
+    #[test]
+    fn test_parse() {
+        let limits = ProcessLimits::from_pid(1).unwrap();
+
+        assert_eq!(
+            limits.open_files,
+            Limit {
+                soft: Some(1024),
+                hard: Some(524288),
+                unit: Some("files".into())
+            }
+        );
+        assert_eq!(limits.cpu_time.soft, None);
+        assert_eq!(limits.nice_priority.unit, None);
+        assert_eq!(limits.realtime_timeout.hard, None);
+    }
+
+    #[test]
+    fn test_report() {
+        let processes = Processes::parse().unwrap();
+
+        let report = report(&processes, 50.0);
+
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].pid, 4242);
+        assert_eq!(report[0].usages.len(), 1);
+        assert_eq!(report[0].usages[0].resource, Resource::OpenFiles);
+        assert_eq!(report[0].usages[0].percent, 75.0);
+    }
+
+    #[test]
+    fn test_threads_by_uid() {
+        // Without user names, processes must still be grouped by their owner
+        let processes = process::parse_with(&ParseOptions {
+            user_name: false,
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(real_uid(4242), Some(0));
+
+        let uids: HashMap<u32, u32> = processes
+            .iter()
+            .filter_map(|process| Some((process.pid(), real_uid(process.pid())?)))
+            .collect();
+        assert_eq!(uids.len(), processes.len());
+
+        let threads = threads_by_uid(&processes, &uids);
+        let total: i32 = processes.iter().map(|process| process.num_threads()).sum();
+        assert_eq!(threads.values().sum::<u64>(), total as u64);
+        assert!(threads[&0] >= 2);
+    }
+}
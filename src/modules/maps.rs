@@ -0,0 +1,311 @@
+use crate::{modules::process::PROC, parser::DataError};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs};
+
+/// Suffix appended by the kernel to the path of files removed since they were mapped.
+pub(crate) const DELETED: &str = " (deleted)";
+
+/// Access permissions of a mapping.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permissions {
+    read: bool,
+    write: bool,
+    execute: bool,
+    /// `true` if the mapping is shared, `false` if private (copy on write).
+    shared: bool,
+}
+
+/// What a mapping is backed by.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MappingKind {
+    /// A file, see [Mapping::pathname].
+    File,
+    /// Anonymous memory, e.g. from `mmap(MAP_ANONYMOUS)`.
+    Anonymous,
+    /// `[heap]`
+    Heap,
+    /// `[stack]`, or `[stack:<tid>]` for thread stacks on older kernels.
+    Stack,
+    /// `[vdso]`
+    Vdso,
+    /// `[vvar]`
+    Vvar,
+    /// `[vsyscall]`
+    Vsyscall,
+    /// Any other pseudo path, such as `[anon:<name>]` or `[uprobes]`.
+    Other(String),
+}
+
+/// A line of `/proc/[pid]/maps`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Mapping {
+    start: u64,
+    end: u64,
+    permissions: Permissions,
+    offset: u64,
+    /// Device of the mapped file, as `major:minor` in hexadecimal.
+    device: String,
+    /// Inode of the mapped file, 0 if not backed by a file.
+    inode: u64,
+    /// Path of the mapped file, or pseudo path such as `[heap]`.
+    pathname: Option<String>,
+    kind: MappingKind,
+    /// Whether the mapped file has been deleted or replaced since it was mapped.
+    deleted: bool,
+}
+
+impl Mapping {
+    pub(crate) fn parse(line: &str) -> Option<Mapping> {
+        let mut rest = line;
+        let mut field = || {
+            let trimmed = rest.trim_start();
+            let end = trimmed.find(' ').unwrap_or(trimmed.len());
+            let (field, remainder) = trimmed.split_at(end);
+            rest = remainder;
+            field
+        };
+
+        let (start, end) = field().split_once('-')?;
+        let perms = field().as_bytes();
+        let offset = field();
+        let device = field().to_string();
+        let inode = field().parse().ok()?;
+
+        if perms.len() != 4 {
+            return None;
+        }
+
+        let pathname = rest.trim_start();
+        let (pathname, deleted) = match pathname.strip_suffix(DELETED) {
+            Some(path) => (path, true),
+            None => (pathname, false),
+        };
+
+        let kind = match pathname {
+            "" => MappingKind::Anonymous,
+            "[heap]" => MappingKind::Heap,
+            "[vdso]" => MappingKind::Vdso,
+            "[vvar]" => MappingKind::Vvar,
+            "[vsyscall]" => MappingKind::Vsyscall,
+            p if p == "[stack]" || p.starts_with("[stack:") => MappingKind::Stack,
+            p if p.starts_with('[') => MappingKind::Other(p.to_string()),
+            _ => MappingKind::File,
+        };
+
+        Some(Mapping {
+            start: u64::from_str_radix(start, 16).ok()?,
+            end: u64::from_str_radix(end, 16).ok()?,
+            permissions: Permissions {
+                read: perms[0] == b'r',
+                write: perms[1] == b'w',
+                execute: perms[2] == b'x',
+                shared: perms[3] == b's',
+            },
+            offset: u64::from_str_radix(offset, 16).ok()?,
+            device,
+            inode,
+            pathname: (!pathname.is_empty()).then(|| pathname.to_string()),
+            kind,
+            deleted,
+        })
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn end(&self) -> u64 {
+        self.end
+    }
+
+    /// Size of the mapping in bytes.
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn permissions(&self) -> Permissions {
+        self.permissions
+    }
+
+    /// Device number of the mapped file, as in `st_dev`.
+    pub fn device(&self) -> Option<u64> {
+        let (major, minor) = self.device.split_once(':')?;
+
+        Some(libc::makedev(
+            u32::from_str_radix(major, 16).ok()?,
+            u32::from_str_radix(minor, 16).ok()?,
+        ))
+    }
+
+    pub fn inode(&self) -> u64 {
+        self.inode
+    }
+
+    pub fn pathname(&self) -> Option<&str> {
+        self.pathname.as_deref()
+    }
+
+    pub fn kind(&self) -> &MappingKind {
+        &self.kind
+    }
+
+    pub fn deleted(&self) -> bool {
+        self.deleted
+    }
+}
+
+impl Permissions {
+    pub fn read(&self) -> bool {
+        self.read
+    }
+
+    pub fn write(&self) -> bool {
+        self.write
+    }
+
+    pub fn execute(&self) -> bool {
+        self.execute
+    }
+
+    pub fn shared(&self) -> bool {
+        self.shared
+    }
+}
+
+/// Aggregated size of a group of mappings.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MappingSummary {
+    /// Number of mappings.
+    count: usize,
+    /// Total size in bytes.
+    size: u64,
+    /// Size of the executable mappings in bytes.
+    executable: u64,
+    /// Size of the writable mappings in bytes.
+    writable: u64,
+}
+
+impl MappingSummary {
+    fn add(&mut self, mapping: &Mapping) {
+        self.count += 1;
+        self.size += mapping.size();
+
+        if mapping.permissions.execute {
+            self.executable += mapping.size();
+        }
+        if mapping.permissions.write {
+            self.writable += mapping.size();
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+/// Rust representation of `/proc/[pid]/maps`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MemoryMaps {
+    mappings: Vec<Mapping>,
+}
+
+impl MemoryMaps {
+    pub fn from_pid(pid: u32) -> Result<MemoryMaps, DataError> {
+        let content = fs::read_to_string(format!("{}{}/maps", PROC, pid))
+            .map_err(|_| DataError::FileNotFound)?;
+
+        let mappings = content
+            .lines()
+            .map(|line| Mapping::parse(line).ok_or(DataError::Parsing))
+            .collect::<Result<_, _>>()?;
+
+        Ok(MemoryMaps { mappings })
+    }
+
+    pub fn mappings(&self) -> &[Mapping] {
+        &self.mappings
+    }
+
+    /// Summarize the mappings per mapped file.
+    pub fn by_file(&self) -> HashMap<&str, MappingSummary> {
+        let mut files: HashMap<&str, MappingSummary> = HashMap::new();
+
+        for mapping in &self.mappings {
+            if let (MappingKind::File, Some(path)) = (&mapping.kind, &mapping.pathname) {
+                files.entry(path).or_default().add(mapping);
+            }
+        }
+
+        files
+    }
+
+    /// Summarize the mappings per kind of region.
+    pub fn by_kind(&self) -> HashMap<&MappingKind, MappingSummary> {
+        let mut kinds: HashMap<&MappingKind, MappingSummary> = HashMap::new();
+
+        for mapping in &self.mappings {
+            kinds.entry(&mapping.kind).or_default().add(mapping);
+        }
+
+        kinds
+    }
+
+    /// Files mapped by the process that have been deleted or replaced on disk.
+    pub fn deleted_files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self
+            .mappings
+            .iter()
+            .filter(|mapping| mapping.deleted && mapping.kind == MappingKind::File)
+            .filter_map(|mapping| mapping.pathname.as_deref())
+            .collect();
+        files.sort_unstable();
+        files.dedup();
+
+        files
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let mapping = Mapping::parse(
+            "7f3a1c000000-7f3a1c021000 rw-s 0001a000 00:05 1234    /dev/shm/my file (deleted)",
+        )
+        .unwrap();
+
+        assert_eq!(mapping.size(), 0x21000);
+        assert_eq!(mapping.offset, 0x1a000);
+        assert!(mapping.permissions.shared && !mapping.permissions.execute);
+        assert_eq!(mapping.pathname(), Some("/dev/shm/my file"));
+        assert!(mapping.deleted);
+    }
+
+    #[test]
+    fn test_from_pid() {
+        let maps = MemoryMaps::from_pid(4242).unwrap();
+
+        assert_eq!(maps.mappings.len(), 13);
+
+        let files = maps.by_file();
+        assert_eq!(files["/usr/sbin/nginx"].count, 3);
+        assert_eq!(files["/usr/sbin/nginx"].executable, 0xc0000);
+
+        let kinds = maps.by_kind();
+        assert_eq!(kinds[&MappingKind::Heap].size, 0x21000);
+        assert_eq!(kinds[&MappingKind::Anonymous].count, 2);
+        assert!(kinds.contains_key(&MappingKind::Vdso));
+        assert!(kinds.contains_key(&MappingKind::Stack));
+
+        assert_eq!(
+            maps.deleted_files(),
+            vec!["/usr/lib/x86_64-linux-gnu/libssl.so.3"]
+        );
+    }
+}
@@ -76,6 +76,18 @@ impl Parser for Memory {
     }
 }
 
+impl Memory {
+    /// Total usable memory in kB.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// Memory available for starting new applications in kB.
+    pub fn available(&self) -> u32 {
+        self.available
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -1,4 +1,4 @@
-use std::{fs, process::Command};
+use std::fs;
 
 use serde::{Deserialize, Serialize};
 
@@ -8,11 +8,17 @@ use crate::parser::Parser;
 const UPTIME: &str = "./mock/uptime";
 #[cfg(not(test))]
 const UPTIME: &str = "/proc/uptime";
+#[cfg(test)]
+const STAT: &str = "./mock/stat";
+#[cfg(not(test))]
+const STAT: &str = "/proc/stat";
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Misc {
-    uptime: Option<f32>,
+    uptime: Option<f64>,
     clk_tck: Option<u8>,
+    /// Boot time in seconds since the Unix epoch.
+    boot_time: Option<u64>,
 }
 
 impl Parser for Misc {
@@ -23,30 +29,48 @@ impl Parser for Misc {
         Ok(Misc {
             uptime: Misc::get_uptime(),
             clk_tck: Misc::get_clktck(),
+            boot_time: Misc::get_boot_time(),
         })
     }
 }
 
 impl Misc {
-    fn get_uptime() -> Option<f32> {
+    /// Time since boot in seconds.
+    pub fn uptime(&self) -> Option<f64> {
+        self.uptime
+    }
+
+    /// Number of clock ticks per second.
+    pub fn clk_tck(&self) -> Option<u8> {
+        self.clk_tck
+    }
+
+    /// Boot time in seconds since the Unix epoch.
+    pub fn boot_time(&self) -> Option<u64> {
+        self.boot_time
+    }
+
+    fn get_uptime() -> Option<f64> {
         fs::read_to_string(UPTIME).ok().and_then(|val| {
             val.split(' ')
                 .next()
-                .and_then(|val| val.parse::<f32>().ok())
+                .and_then(|val| val.parse::<f64>().ok())
+        })
+    }
+
+    fn get_boot_time() -> Option<u64> {
+        fs::read_to_string(STAT).ok().and_then(|val| {
+            val.lines()
+                .find_map(|line| line.strip_prefix("btime "))
+                .and_then(|val| val.trim().parse::<u64>().ok())
         })
     }
 
     fn get_clktck() -> Option<u8> {
-        Command::new("getconf")
-            .arg("CLK_TCK")
-            .output()
-            .ok()
-            .and_then(|r| {
-                std::str::from_utf8(&r.stdout)
-                    .ok()
-                    .map(|s| String::from(s).replace('\n', ""))
-            })
-            .and_then(|res| res.parse::<u8>().ok())
+        // SAFETY: sysconf has no memory safety preconditions.
+        let clk_tck = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+
+        u8::try_from(clk_tck).ok()
     }
 }
 
@@ -64,5 +88,11 @@ mod test {
         let up = Misc::get_uptime();
 
         assert_ne!(up, None);
+        assert_eq!(up, Some(101822.33));
+    }
+
+    #[test]
+    fn test_get_boot_time() {
+        assert_eq!(Misc::get_boot_time(), Some(1700000000));
     }
 }
@@ -1,5 +1,17 @@
+pub mod cgroup;
+pub mod container;
 pub mod cpu;
+pub mod decode;
+pub mod diff;
+pub mod events;
+pub mod handle;
+pub mod limits;
+pub mod maps;
 pub mod memory;
 pub mod misc;
+pub mod monitor;
+pub mod namespace;
+pub(crate) mod netlink;
 pub mod network;
 pub mod process;
+pub mod stale;
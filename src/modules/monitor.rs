@@ -0,0 +1,448 @@
+use crate::{
+    helper::page_size,
+    modules::{
+        decode::Signal,
+        misc::Misc,
+        process::{self, ParseOptions, Process, PROC},
+    },
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io, mem,
+    process::{Child, Command},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// I/O counters of a process, from `/proc/[pid]/io`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProcessIo {
+    /// Bytes read, including from the page cache.
+    rchar: u64,
+    /// Bytes written, including to the page cache.
+    wchar: u64,
+    /// Read syscalls.
+    syscr: u64,
+    /// Write syscalls.
+    syscw: u64,
+    /// Bytes fetched from the storage layer.
+    read_bytes: u64,
+    /// Bytes sent to the storage layer.
+    write_bytes: u64,
+    /// Bytes whose write was cancelled by truncating dirty page cache.
+    cancelled_write_bytes: u64,
+}
+
+impl ProcessIo {
+    /// Read the I/O counters of a process, which requires the same
+    /// permissions as `ptrace`.
+    pub fn from_pid(pid: u32) -> Result<ProcessIo, DataError> {
+        let values = read_keyed(&format!("{}{}/io", PROC, pid)).map_err(|e| match e.kind() {
+            io::ErrorKind::PermissionDenied => DataError::PermissionDenied,
+            _ => DataError::ProcessExited,
+        })?;
+        let value = |key: &str| values.get(key).copied().ok_or(DataError::Parsing);
+
+        Ok(ProcessIo {
+            rchar: value("rchar")?,
+            wchar: value("wchar")?,
+            syscr: value("syscr")?,
+            syscw: value("syscw")?,
+            read_bytes: value("read_bytes")?,
+            write_bytes: value("write_bytes")?,
+            cancelled_write_bytes: value("cancelled_write_bytes")?,
+        })
+    }
+
+    pub fn rchar(&self) -> u64 {
+        self.rchar
+    }
+
+    pub fn wchar(&self) -> u64 {
+        self.wchar
+    }
+
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes
+    }
+
+    pub fn write_bytes(&self) -> u64 {
+        self.write_bytes
+    }
+
+    fn add(&mut self, other: &ProcessIo) {
+        self.rchar += other.rchar;
+        self.wchar += other.wchar;
+        self.syscr += other.syscr;
+        self.syscw += other.syscw;
+        self.read_bytes += other.read_bytes;
+        self.write_bytes += other.write_bytes;
+        self.cancelled_write_bytes += other.cancelled_write_bytes;
+    }
+}
+
+/// Read a file made of `key: value` lines, with an optional `kB` unit which
+/// is converted to bytes.
+fn read_keyed(path: &str) -> io::Result<HashMap<String, u64>> {
+    let content = fs::read_to_string(path)?;
+
+    Ok(content
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let value = value.trim();
+
+            let value = match value.strip_suffix(" kB") {
+                Some(kb) => kb.trim().parse::<u64>().ok()? * 1024,
+                None => value.parse().ok()?,
+            };
+
+            Some((key.to_string(), value))
+        })
+        .collect())
+}
+
+/// Proportional set size of a process in bytes, from `/proc/[pid]/smaps_rollup`.
+fn pss(pid: u32) -> Option<u64> {
+    read_keyed(&format!("{}{}/smaps_rollup", PROC, pid))
+        .ok()?
+        .get("Pss")
+        .copied()
+}
+
+/// Resource usage of a process tree at one point in time.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sample {
+    /// Time since the command was spawned.
+    elapsed: Duration,
+    /// Number of processes in the tree.
+    processes: usize,
+    threads: u64,
+    /// Resident set size in bytes, summed over the tree.
+    rss: u64,
+    /// Proportional set size in bytes, summed over the processes that
+    /// could be read.
+    pss: u64,
+    /// CPU time of the live processes in the tree.
+    cpu_time: Duration,
+    /// I/O of the live processes in the tree. As the kernel adds the I/O of
+    /// reaped children to their parent, this is not a sum over processes.
+    io: ProcessIo,
+}
+
+impl Sample {
+    /// Measure the processes of the tree rooted at `root`.
+    fn take(root: u32, processes: &[Process], misc: Option<&Misc>) -> Sample {
+        let page_size = page_size();
+        let mut sample = Sample::default();
+
+        for process in descendants(root, processes) {
+            sample.processes += 1;
+            sample.threads += process.num_threads().max(0) as u64;
+            sample.rss += process.rss().max(0) as u64 * page_size;
+            sample.pss += pss(process.pid()).unwrap_or_default();
+            sample.cpu_time += misc
+                .and_then(|misc| process.cpu_time(misc))
+                .unwrap_or_default();
+
+            if let Ok(io) = ProcessIo::from_pid(process.pid()) {
+                sample.io.add(&io);
+            }
+        }
+
+        sample
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn processes(&self) -> usize {
+        self.processes
+    }
+
+    pub fn threads(&self) -> u64 {
+        self.threads
+    }
+
+    pub fn rss(&self) -> u64 {
+        self.rss
+    }
+
+    pub fn pss(&self) -> u64 {
+        self.pss
+    }
+
+    pub fn cpu_time(&self) -> Duration {
+        self.cpu_time
+    }
+
+    pub fn io(&self) -> &ProcessIo {
+        &self.io
+    }
+}
+
+/// The process `root` and all of its descendants.
+fn descendants(root: u32, processes: &[Process]) -> Vec<&Process> {
+    let mut children: HashMap<u32, Vec<&Process>> = HashMap::new();
+    for process in processes {
+        children.entry(process.ppid()).or_default().push(process);
+    }
+
+    let mut tree: Vec<&Process> = processes.iter().filter(|p| p.pid() == root).collect();
+    let mut i = 0;
+    while let Some(process) = tree.get(i) {
+        if let Some(children) = children.get(&process.pid()) {
+            tree.extend(children);
+        }
+        i += 1;
+    }
+
+    tree
+}
+
+/// Resource usage of a command over its lifetime, returned by [run].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Usage {
+    /// Exit code, if the command exited normally.
+    code: Option<i32>,
+    /// Signal that killed the command, if any.
+    signal: Option<Signal>,
+    /// Wall clock time until the command exited.
+    elapsed: Duration,
+    /// CPU time spent in user mode by the command and its reaped descendants.
+    user_time: Duration,
+    /// CPU time spent in kernel mode by the command and its reaped descendants.
+    system_time: Duration,
+    /// Largest resident set size of a single process of the tree, in bytes.
+    max_rss: u64,
+    /// Largest resident set size of the whole tree seen in a sample, in bytes.
+    peak_rss: u64,
+    /// Largest proportional set size of the whole tree seen in a sample, in bytes.
+    peak_pss: u64,
+    /// Largest number of threads of the tree seen in a sample.
+    max_threads: u64,
+    /// Bytes read from storage by the command and its reaped descendants.
+    read_bytes: u64,
+    /// Bytes written to storage by the command and its reaped descendants.
+    write_bytes: u64,
+    samples: Vec<Sample>,
+}
+
+impl Usage {
+    pub fn code(&self) -> Option<i32> {
+        self.code
+    }
+
+    pub fn signal(&self) -> Option<Signal> {
+        self.signal
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Total CPU time, in user and kernel mode.
+    pub fn cpu_time(&self) -> Duration {
+        self.user_time + self.system_time
+    }
+
+    pub fn user_time(&self) -> Duration {
+        self.user_time
+    }
+
+    pub fn system_time(&self) -> Duration {
+        self.system_time
+    }
+
+    pub fn max_rss(&self) -> u64 {
+        self.max_rss
+    }
+
+    pub fn peak_rss(&self) -> u64 {
+        self.peak_rss
+    }
+
+    pub fn peak_pss(&self) -> u64 {
+        self.peak_pss
+    }
+
+    pub fn max_threads(&self) -> u64 {
+        self.max_threads
+    }
+
+    pub fn read_bytes(&self) -> u64 {
+        self.read_bytes
+    }
+
+    pub fn write_bytes(&self) -> u64 {
+        self.write_bytes
+    }
+
+    /// Samples of the process tree, in chronological order.
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+}
+
+fn duration(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec as u64) + Duration::from_micros(time.tv_usec as u64)
+}
+
+/// Spawn `command` and sample it and its descendants every `interval` until it exits.
+///
+/// Totals come from `wait4(2)` and account for every reaped descendant,
+/// while peaks are only as precise as the sampling interval.
+///
+/// The child is not returned, so its standard streams should be inherited
+/// or null. Use [watch] to read from piped streams.
+pub fn run(command: &mut Command, interval: Duration) -> Result<Usage, DataError> {
+    let mut child = command.spawn().map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => DataError::PermissionDenied,
+        _ => DataError::FileNotFound,
+    })?;
+
+    watch(&mut child, interval)
+}
+
+/// Sample a spawned child and its descendants every `interval` until it exits,
+/// see [run].
+///
+/// Piped streams stay in `child`. As the child blocks when a pipe is full,
+/// take them beforehand and read them from another thread if the output may
+/// be large. The child is reaped, so [Child::wait] fails afterwards.
+pub fn watch(child: &mut Child, interval: Duration) -> Result<Usage, DataError> {
+    let misc = Misc::parse().ok();
+    let start = Instant::now();
+    let pid = child.id();
+
+    let mut samples: Vec<Sample> = vec![];
+    let mut status: libc::c_int = 0;
+    // SAFETY: rusage is plain data, all zeroes is a valid value.
+    let mut rusage: libc::rusage = unsafe { mem::zeroed() };
+
+    loop {
+        if let Ok(processes) = process::parse_with(&ParseOptions::minimal()) {
+            let sample = Sample {
+                elapsed: start.elapsed(),
+                ..Sample::take(pid, &processes, misc.as_ref())
+            };
+            if sample.processes > 0 {
+                samples.push(sample);
+            }
+        }
+
+        // The child is reaped here rather than through `Child::wait`, which
+        // does not report the resource usage.
+        // SAFETY: status and rusage are valid for writes.
+        let res =
+            unsafe { libc::wait4(pid as libc::pid_t, &mut status, libc::WNOHANG, &mut rusage) };
+
+        match res {
+            0 => thread::sleep(interval),
+            res if res < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {}
+            res if res < 0 => return Err(DataError::ProcessExited),
+            _ => break,
+        }
+    }
+
+    // Blocks of 512 bytes, refer to `getrusage(2)`.
+    const BLOCK: u64 = 512;
+
+    Ok(Usage {
+        code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+        signal: libc::WIFSIGNALED(status)
+            .then(|| Signal::from_number(libc::WTERMSIG(status) as u8))
+            .flatten(),
+        elapsed: start.elapsed(),
+        user_time: duration(rusage.ru_utime),
+        system_time: duration(rusage.ru_stime),
+        // Reported in kilobytes
+        max_rss: rusage.ru_maxrss as u64 * 1024,
+        peak_rss: samples.iter().map(|s| s.rss).max().unwrap_or_default(),
+        peak_pss: samples.iter().map(|s| s.pss).max().unwrap_or_default(),
+        max_threads: samples.iter().map(|s| s.threads).max().unwrap_or_default(),
+        read_bytes: rusage.ru_inblock as u64 * BLOCK,
+        write_bytes: rusage.ru_oublock as u64 * BLOCK,
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{io::Read, process::Stdio};
+
+    #[test]
+    fn test_io() {
+        let io = ProcessIo::from_pid(4242).unwrap();
+
+        assert_eq!(io.rchar, 48213327);
+        assert_eq!(io.write_bytes, 4096000);
+        assert_eq!(pss(4242), Some(3264 * 1024));
+        assert_eq!(pss(1), None);
+    }
+
+    #[test]
+    fn test_sample() {
+        let processes = process::parse_with(&ParseOptions::minimal()).unwrap();
+
+        assert_eq!(descendants(1, &processes).len(), 1);
+        assert_eq!(descendants(2, &processes).len(), 0);
+
+        let sample = Sample::take(4242, &processes, Some(&Misc::parse().unwrap()));
+
+        assert_eq!(sample.processes, 1);
+        assert_eq!(sample.rss, 1530 * page_size());
+        assert_eq!(sample.pss, 3264 * 1024);
+        assert_eq!(sample.io.read_bytes, 1265664);
+    }
+
+    #[test]
+    fn test_run() {
+        let interval = Duration::from_millis(10);
+
+        let usage = run(Command::new("sh").args(["-c", "exit 3"]), interval).unwrap();
+        assert_eq!(usage.code(), Some(3));
+        assert_eq!(usage.signal(), None);
+        assert!(usage.elapsed() < Duration::from_secs(10));
+        assert!(usage.max_rss() > 0);
+
+        let usage = run(Command::new("sh").args(["-c", "kill -9 $$"]), interval).unwrap();
+        assert_eq!(usage.code(), None);
+        assert_eq!(usage.signal(), Some(Signal::SIGKILL));
+
+        let usage = run(Command::new("sleep").arg("0.05"), interval).unwrap();
+        assert_eq!(usage.code(), Some(0));
+        assert!(usage.elapsed() >= Duration::from_millis(50));
+
+        assert!(matches!(
+            run(&mut Command::new("./mock/missing"), interval),
+            Err(DataError::FileNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_watch() {
+        let mut child = Command::new("echo")
+            .arg("hello")
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let usage = watch(&mut child, Duration::from_millis(10)).unwrap();
+        assert_eq!(usage.code(), Some(0));
+
+        let mut output = String::new();
+        child
+            .stdout
+            .take()
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+        assert_eq!(output, "hello\n");
+        assert!(child.wait().is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use crate::modules::process::{Process, PROC};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs};
+
+/// Inode of the initial pid namespace, fixed by the kernel in `include/linux/proc_ns.h`.
+pub(crate) const PROC_PID_INIT_INO: u64 = 0xEFFF_FFFC;
+
+/// The types of namespaces found in `/proc/[pid]/ns/`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NamespaceKind {
+    Pid,
+    Net,
+    Mnt,
+    Uts,
+    Ipc,
+    User,
+    Cgroup,
+    Time,
+}
+
+impl NamespaceKind {
+    /// Name of the link in `/proc/[pid]/ns/`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamespaceKind::Pid => "pid",
+            NamespaceKind::Net => "net",
+            NamespaceKind::Mnt => "mnt",
+            NamespaceKind::Uts => "uts",
+            NamespaceKind::Ipc => "ipc",
+            NamespaceKind::User => "user",
+            NamespaceKind::Cgroup => "cgroup",
+            NamespaceKind::Time => "time",
+        }
+    }
+}
+
+/// Inode numbers identifying the namespaces of a process.
+///
+/// Two processes share a namespace if they have the same inode for it.
+/// A value is `None` if the link could not be read, e.g. for lack of permissions
+/// or because the kernel does not support this type of namespace.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Namespaces {
+    pid: Option<u64>,
+    net: Option<u64>,
+    mnt: Option<u64>,
+    uts: Option<u64>,
+    ipc: Option<u64>,
+    user: Option<u64>,
+    cgroup: Option<u64>,
+    time: Option<u64>,
+}
+
+impl Namespaces {
+    /// Read the namespaces of a process from the links in `/proc/[pid]/ns/`.
+    pub fn from_pid(pid: u32) -> Namespaces {
+        let read = |kind: NamespaceKind| {
+            let link = fs::read_link(format!("{}{}/ns/{}", PROC, pid, kind.name())).ok()?;
+
+            // The link target has the form `type:[inode]`
+            link.to_str()?
+                .split_once(":[")?
+                .1
+                .trim_end_matches(']')
+                .parse()
+                .ok()
+        };
+
+        Namespaces {
+            pid: read(NamespaceKind::Pid),
+            net: read(NamespaceKind::Net),
+            mnt: read(NamespaceKind::Mnt),
+            uts: read(NamespaceKind::Uts),
+            ipc: read(NamespaceKind::Ipc),
+            user: read(NamespaceKind::User),
+            cgroup: read(NamespaceKind::Cgroup),
+            time: read(NamespaceKind::Time),
+        }
+    }
+
+    /// Inode of the namespace of the given type.
+    pub fn get(&self, kind: NamespaceKind) -> Option<u64> {
+        match kind {
+            NamespaceKind::Pid => self.pid,
+            NamespaceKind::Net => self.net,
+            NamespaceKind::Mnt => self.mnt,
+            NamespaceKind::Uts => self.uts,
+            NamespaceKind::Ipc => self.ipc,
+            NamespaceKind::User => self.user,
+            NamespaceKind::Cgroup => self.cgroup,
+            NamespaceKind::Time => self.time,
+        }
+    }
+}
+
+/// Read the `NSpid` and `NStgid` chains from `/proc/[pid]/status`.
+///
+/// Each chain starts with the id in the namespace of `/proc` and ends with
+/// the id in the innermost pid namespace of the process.
+pub(crate) fn read_ns_ids(pid: u32) -> (Vec<u32>, Vec<u32>) {
+    let Ok(status) = fs::read_to_string(format!("{}{}/status", PROC, pid)) else {
+        return (vec![], vec![]);
+    };
+
+    let chain = |key: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(|ids| {
+                ids.split_whitespace()
+                    .filter_map(|id| id.parse().ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    (chain("NSpid:"), chain("NStgid:"))
+}
+
+/// Group processes by the inode of their namespace of the given type.
+///
+/// Processes whose namespace could not be read are left out.
+pub fn group_by(processes: &[Process], kind: NamespaceKind) -> HashMap<u64, Vec<&Process>> {
+    let mut groups: HashMap<u64, Vec<&Process>> = HashMap::new();
+
+    for process in processes {
+        if let Some(inode) = process.namespaces().get(kind) {
+            groups.entry(inode).or_default().push(process);
+        }
+    }
+
+    groups
+}
+
+/// Translate a pid as seen from the host into the pid inside the innermost
+/// pid namespace of the process (e.g. the pid seen inside its container).
+pub fn to_namespace_pid(processes: &[Process], host_pid: u32) -> Option<u32> {
+    processes
+        .iter()
+        .find(|process| process.pid() == host_pid)
+        .and_then(|process| process.nspid().last().copied())
+}
+
+/// Translate a pid inside the pid namespace `namespace` into the pid seen from the host.
+pub fn to_host_pid(processes: &[Process], namespace: u64, ns_pid: u32) -> Option<u32> {
+    processes
+        .iter()
+        .find(|process| {
+            process.namespaces().pid == Some(namespace) && process.nspid().last() == Some(&ns_pid)
+        })
+        .map(|process| process.pid())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{modules::process::Processes, parser::Parser};
+
+    #[test]
+    fn test_from_pid() {
+        let ns = Namespaces::from_pid(4242);
+
+        assert_eq!(ns.get(NamespaceKind::Pid), Some(4026532455));
+        assert_eq!(ns.get(NamespaceKind::Net), Some(4026532458));
+        assert_eq!(ns.get(NamespaceKind::Time), Some(4026531834));
+    }
+
+    #[test]
+    fn test_group_and_translate() {
+        let processes = Processes::parse().unwrap();
+
+        let net = group_by(&processes, NamespaceKind::Net);
+        assert_eq!(net.len(), 2);
+        let time = group_by(&processes, NamespaceKind::Time);
+        assert_eq!(time[&4026531834].len(), 2);
+
+        assert_eq!(to_namespace_pid(&processes, 4242), Some(1));
+        assert_eq!(to_namespace_pid(&processes, 1), Some(1));
+        assert_eq!(to_host_pid(&processes, 4026532455, 1), Some(4242));
+        assert_eq!(to_host_pid(&processes, 4026531836, 1), Some(1));
+    }
+}
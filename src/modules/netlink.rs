@@ -0,0 +1,228 @@
+use std::{
+    io, mem,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    time::Duration,
+};
+
+/// Size of `struct nlmsghdr`.
+pub(crate) const NLMSG_HDRLEN: usize = 16;
+/// Size of `struct rtattr`.
+const RTA_HDRLEN: usize = 4;
+
+/// Round up to the alignment of netlink messages and attributes.
+fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+pub(crate) fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_ne_bytes(bytes.try_into().unwrap()))
+}
+
+/// Open a netlink socket of the given protocol, e.g. `NETLINK_ROUTE`,
+/// subscribed to the multicast `groups`.
+pub(crate) fn socket(protocol: libc::c_int, groups: u32) -> io::Result<OwnedFd> {
+    // SAFETY: socket has no memory safety preconditions.
+    let fd = unsafe {
+        libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+            protocol,
+        )
+    };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: the fd was just created and is not owned by anything else.
+    let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+
+    // SAFETY: sockaddr_nl is plain data, all zeroes is a valid value.
+    let mut address: libc::sockaddr_nl = unsafe { mem::zeroed() };
+    address.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+    address.nl_groups = groups;
+
+    // SAFETY: the address is a valid sockaddr_nl of the given size.
+    let res = unsafe {
+        libc::bind(
+            socket.as_raw_fd(),
+            &address as *const libc::sockaddr_nl as *const libc::sockaddr,
+            mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        )
+    };
+    if res < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(socket)
+}
+
+/// Send a message built with [message].
+pub(crate) fn send(socket: &OwnedFd, message: &[u8]) -> io::Result<()> {
+    // SAFETY: the message is valid for reads of its length.
+    let sent = unsafe {
+        libc::send(
+            socket.as_raw_fd(),
+            message.as_ptr() as *const libc::c_void,
+            message.len(),
+            0,
+        )
+    };
+    if sent < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Make [recv] fail with `EAGAIN` when nothing arrives within `timeout`,
+/// or block indefinitely if `None`.
+pub(crate) fn set_timeout(socket: &OwnedFd, timeout: Option<Duration>) -> io::Result<()> {
+    let timeout = timeout.unwrap_or_default();
+    let timeval = libc::timeval {
+        tv_sec: timeout.as_secs() as libc::time_t,
+        tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+    };
+
+    // SAFETY: timeval is valid for reads of its size.
+    let res = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_RCVTIMEO,
+            &timeval as *const libc::timeval as *const libc::c_void,
+            mem::size_of::<libc::timeval>() as libc::socklen_t,
+        )
+    };
+    if res < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Receive a datagram, retrying when interrupted.
+pub(crate) fn recv(socket: &OwnedFd, buffer: &mut [u8]) -> io::Result<usize> {
+    loop {
+        // SAFETY: the buffer is valid for writes of its length.
+        let len = unsafe {
+            libc::recv(
+                socket.as_raw_fd(),
+                buffer.as_mut_ptr() as *mut libc::c_void,
+                buffer.len(),
+                0,
+            )
+        };
+
+        if len >= 0 {
+            return Ok(len as usize);
+        }
+
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+}
+
+/// Dump a table of the kernel, e.g. `RTM_GETADDR`, and return the payload of
+/// every message of the answer along with its type.
+pub(crate) fn dump(message_type: u16, payload: &[u8]) -> io::Result<Vec<(u16, Vec<u8>)>> {
+    let socket = socket(libc::NETLINK_ROUTE, 0)?;
+
+    let flags = (libc::NLM_F_REQUEST | libc::NLM_F_DUMP) as u16;
+    send(&socket, &message(message_type, flags, payload))?;
+
+    let mut answer = vec![];
+    let mut buffer = vec![0u8; 32 * 1024];
+
+    loop {
+        let len = recv(&socket, &mut buffer)?;
+
+        for (message_type, payload) in messages(&buffer[..len]) {
+            match message_type as libc::c_int {
+                libc::NLMSG_DONE => return Ok(answer),
+                libc::NLMSG_ERROR => {
+                    let errno = u32_at(payload, 0).unwrap_or_default() as i32;
+                    return Err(io::Error::from_raw_os_error(-errno));
+                }
+                _ => answer.push((message_type, payload.to_vec())),
+            }
+        }
+    }
+}
+
+/// Split a datagram into its messages, yielding their type and payload.
+pub(crate) fn messages(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let len = u32_at(data, offset)? as usize;
+        if len < NLMSG_HDRLEN || offset + len > data.len() {
+            return None;
+        }
+
+        let message_type = u16_at(data, offset + 4)?;
+        let payload = &data[offset + NLMSG_HDRLEN..offset + len];
+        offset += align(len);
+
+        Some((message_type, payload))
+    })
+}
+
+/// Split the attributes following the fixed header of a message, yielding
+/// their type and value.
+pub(crate) fn attributes(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let len = u16_at(data, offset)? as usize;
+        if len < RTA_HDRLEN || offset + len > data.len() {
+            return None;
+        }
+
+        let attribute_type = u16_at(data, offset + 2)?;
+        let value = &data[offset + RTA_HDRLEN..offset + len];
+        offset += align(len);
+
+        Some((attribute_type, value))
+    })
+}
+
+/// Decode a null terminated string attribute.
+pub(crate) fn string(value: &[u8]) -> String {
+    let end = value.iter().position(|&c| c == 0).unwrap_or(value.len());
+
+    String::from_utf8_lossy(&value[..end]).into_owned()
+}
+
+/// Build a message with a `struct nlmsghdr`, the sequence number and port id
+/// are left to zero.
+pub(crate) fn message(message_type: u16, flags: u16, payload: &[u8]) -> Vec<u8> {
+    let len = NLMSG_HDRLEN + payload.len();
+    let mut message = Vec::with_capacity(align(len));
+    message.extend((len as u32).to_ne_bytes());
+    message.extend(message_type.to_ne_bytes());
+    message.extend(flags.to_ne_bytes());
+    message.extend([0; 8]);
+    message.extend(payload);
+    message.resize(align(len), 0);
+
+    message
+}
+
+#[cfg(test)]
+pub(crate) fn attribute(attribute_type: u16, value: &[u8]) -> Vec<u8> {
+    let len = RTA_HDRLEN + value.len();
+    let mut attribute = vec![];
+    attribute.extend((len as u16).to_ne_bytes());
+    attribute.extend(attribute_type.to_ne_bytes());
+    attribute.extend(value);
+    attribute.resize(align(len), 0);
+
+    attribute
+}
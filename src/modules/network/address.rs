@@ -0,0 +1,396 @@
+use super::route::Routes;
+use crate::{
+    modules::{netlink, process::PROC},
+    parser::Parser,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+/// Message types and attributes of `include/uapi/linux/if_addr.h`.
+pub(crate) const RTM_NEWADDR: u16 = 20;
+pub(crate) const RTM_DELADDR: u16 = 21;
+const RTM_GETADDR: u16 = 22;
+const IFA_ADDRESS: u16 = 1;
+pub(crate) const IFA_LOCAL: u16 = 2;
+pub(crate) const IFA_LABEL: u16 = 3;
+
+/// Size of `struct ifaddrmsg`.
+const IFADDRMSG_LEN: usize = 8;
+
+/// Netlink answers for the network namespace of the current process, which
+/// the mock filesystem does not describe.
+#[cfg(test)]
+const NETLINK: bool = false;
+#[cfg(not(test))]
+const NETLINK: bool = true;
+
+/// Reach of an address.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Site,
+    Link,
+    Host,
+    /// Any other `RT_SCOPE_*` value.
+    Other(u8),
+}
+
+impl Scope {
+    /// Decode a `RT_SCOPE_*` value of `include/uapi/linux/rtnetlink.h`.
+    fn from_rt_scope(scope: u8) -> Scope {
+        match scope {
+            0 => Scope::Global,
+            200 => Scope::Site,
+            253 => Scope::Link,
+            254 => Scope::Host,
+            scope => Scope::Other(scope),
+        }
+    }
+
+    /// Decode the scope of `/proc/net/if_inet6`, from the `IPV6_ADDR_*`
+    /// constants of `include/net/ipv6.h`.
+    fn from_ipv6_scope(scope: u8) -> Scope {
+        match scope {
+            0x00 => Scope::Global,
+            0x10 => Scope::Host,
+            0x20 => Scope::Link,
+            0x40 => Scope::Site,
+            scope => Scope::Other(scope),
+        }
+    }
+}
+
+/// An address assigned to an interface.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceAddress {
+    address: IpAddr,
+    prefix_len: u8,
+    scope: Scope,
+}
+
+impl InterfaceAddress {
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    pub fn scope(&self) -> Scope {
+        self.scope
+    }
+}
+
+/// Addresses of the interfaces, by interface name.
+///
+/// IPv4 addresses are read with a `RTM_GETADDR` netlink dump, or from
+/// `/proc/net/fib_trie` if netlink is unavailable. IPv6 addresses are read
+/// from `/proc/net/if_inet6`.
+pub fn addresses() -> HashMap<String, Vec<InterfaceAddress>> {
+    let ipv4 = if NETLINK { ipv4_netlink().ok() } else { None };
+
+    with_ipv4(ipv4)
+}
+
+/// Complete the IPv4 addresses read with netlink, reading them from
+/// `/proc/net/fib_trie` when `None`, with the IPv6 addresses.
+fn with_ipv4(
+    ipv4: Option<HashMap<String, Vec<InterfaceAddress>>>,
+) -> HashMap<String, Vec<InterfaceAddress>> {
+    let mut addresses = ipv4.or_else(ipv4_fib_trie).unwrap_or_default();
+
+    for (interface, address) in ipv6().unwrap_or_default() {
+        addresses.entry(interface).or_default().push(address);
+    }
+
+    addresses
+}
+
+/// Dump the IPv4 addresses with netlink.
+fn ipv4_netlink() -> io::Result<HashMap<String, Vec<InterfaceAddress>>> {
+    let mut request = [0u8; IFADDRMSG_LEN];
+    request[0] = libc::AF_INET as u8;
+
+    Ok(from_dump(netlink::dump(RTM_GETADDR, &request)?))
+}
+
+/// Group the addresses of the messages answering a `RTM_GETADDR` dump by
+/// interface.
+fn from_dump(messages: Vec<(u16, Vec<u8>)>) -> HashMap<String, Vec<InterfaceAddress>> {
+    let mut addresses: HashMap<String, Vec<InterfaceAddress>> = HashMap::new();
+
+    for (message_type, payload) in messages {
+        if message_type != RTM_NEWADDR {
+            continue;
+        }
+
+        let Some((index, label, address)) = parse_message(&payload) else {
+            continue;
+        };
+
+        if let Some(interface) = label.or_else(|| interface_name(index)) {
+            addresses.entry(interface).or_default().push(address);
+        }
+    }
+
+    addresses
+}
+
+/// Decode a `RTM_NEWADDR` or `RTM_DELADDR` message into the index of the
+/// interface, its name if the message has a label, and the address.
+pub(crate) fn parse_message(payload: &[u8]) -> Option<(u32, Option<String>, InterfaceAddress)> {
+    let header = payload.get(..IFADDRMSG_LEN)?;
+    let (family, prefix_len, scope) = (header[0], header[1], header[3]);
+    let index = netlink::u32_at(header, 4)?;
+
+    let (mut local, mut address, mut label) = (None, None, None);
+    for (attribute, value) in netlink::attributes(&payload[IFADDRMSG_LEN..]) {
+        match attribute {
+            IFA_LOCAL => local = Some(value),
+            IFA_ADDRESS => address = Some(value),
+            IFA_LABEL => label = Some(netlink::string(value)),
+            _ => {}
+        }
+    }
+
+    // On point to point links IFA_ADDRESS is the peer, IFA_LOCAL the local address
+    let address = match (family as i32, local.or(address)?) {
+        (libc::AF_INET, bytes) => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        (libc::AF_INET6, bytes) => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => return None,
+    };
+
+    // Labels of aliases are suffixed, e.g. `eth0:1`
+    let interface = label.map(|label| label.split(':').next().unwrap_or_default().to_string());
+
+    Some((
+        index,
+        interface,
+        InterfaceAddress {
+            address,
+            prefix_len,
+            scope: Scope::from_rt_scope(scope),
+        },
+    ))
+}
+
+/// Name of the interface with the given index.
+pub(crate) fn interface_name(index: u32) -> Option<String> {
+    let mut name = [0 as libc::c_char; libc::IF_NAMESIZE];
+
+    // SAFETY: the buffer is IF_NAMESIZE long, as required.
+    let res = unsafe { libc::if_indextoname(index, name.as_mut_ptr()) };
+    if res.is_null() {
+        return None;
+    }
+
+    let name: Vec<u8> = name
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+
+    Some(String::from_utf8_lossy(&name).into_owned())
+}
+
+/// Find the local IPv4 addresses in `/proc/net/fib_trie`.
+///
+/// The trie does not name interfaces, so they are taken from the link route
+/// of the network of every address.
+fn ipv4_fib_trie() -> Option<HashMap<String, Vec<InterfaceAddress>>> {
+    let trie = fs::read_to_string(format!("{}net/fib_trie", PROC)).ok()?;
+    let routes = Routes::parse().ok()?;
+
+    // A leaf is a line `|-- <ip>` followed by one line per prefix
+    let mut local: Vec<Ipv4Addr> = vec![];
+    let mut leaf: Option<Ipv4Addr> = None;
+    for line in trie.lines().map(str::trim) {
+        if let Some(ip) = line.strip_prefix("|-- ") {
+            leaf = ip.parse().ok();
+        } else if line == "/32 host LOCAL" {
+            local.extend(leaf);
+        }
+    }
+    local.sort_unstable();
+    local.dedup();
+
+    let mut addresses: HashMap<String, Vec<InterfaceAddress>> = HashMap::new();
+
+    for ip in local {
+        let address = IpAddr::V4(ip);
+
+        let (interface, prefix_len) = if ip.is_loopback() {
+            // Routes of the loopback are in the local table only
+            ("lo".to_string(), 8)
+        } else {
+            let Some(route) = routes
+                .routes()
+                .iter()
+                .filter(|route| route.gateway().is_none() && route.contains(&address))
+                .max_by_key(|route| route.prefix_len())
+            else {
+                continue;
+            };
+
+            (route.interface().to_string(), route.prefix_len())
+        };
+
+        addresses
+            .entry(interface)
+            .or_default()
+            .push(InterfaceAddress {
+                address,
+                prefix_len,
+                scope: if ip.is_loopback() {
+                    Scope::Host
+                } else {
+                    Scope::Global
+                },
+            });
+    }
+
+    Some(addresses)
+}
+
+/// Read the IPv6 addresses of `/proc/net/if_inet6`, made of lines such as
+/// `fe800000000000003e58c2fffe9a1f07 03 40 20 80 wlan0`.
+fn ipv6() -> Option<Vec<(String, InterfaceAddress)>> {
+    let content = fs::read_to_string(format!("{}net/if_inet6", PROC)).ok()?;
+
+    content
+        .lines()
+        .map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 6 {
+                return None;
+            }
+
+            Some((
+                fields[5].to_string(),
+                InterfaceAddress {
+                    address: IpAddr::V6(Ipv6Addr::from(u128::from_str_radix(fields[0], 16).ok()?)),
+                    prefix_len: u8::from_str_radix(fields[2], 16).ok()?,
+                    scope: Scope::from_ipv6_scope(u8::from_str_radix(fields[3], 16).ok()?),
+                },
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_addresses() {
+        let addresses = addresses();
+
+        assert_eq!(
+            addresses["wlan0"],
+            vec![
+                InterfaceAddress {
+                    address: "192.168.1.14".parse().unwrap(),
+                    prefix_len: 24,
+                    scope: Scope::Global
+                },
+                InterfaceAddress {
+                    address: "fe80::3e58:c2ff:fe9a:1f07".parse().unwrap(),
+                    prefix_len: 64,
+                    scope: Scope::Link
+                },
+                InterfaceAddress {
+                    address: "2a01:4f8:c0c2:b1e1::15".parse().unwrap(),
+                    prefix_len: 64,
+                    scope: Scope::Global
+                },
+            ]
+        );
+        assert_eq!(addresses["lo"].len(), 2);
+        assert_eq!(addresses["lo"][1].scope, Scope::Host);
+    }
+
+    #[test]
+    fn test_parse_message() {
+        let mut payload = vec![libc::AF_INET as u8, 16, 0, 0];
+        payload.extend(7u32.to_ne_bytes());
+        payload.extend(netlink::attribute(IFA_ADDRESS, &[10, 8, 0, 2]));
+        payload.extend(netlink::attribute(IFA_LOCAL, &[10, 8, 0, 1]));
+        payload.extend(netlink::attribute(IFA_LABEL, b"tun0:1\0"));
+
+        let message = netlink::message(RTM_NEWADDR, 0, &payload);
+        let (message_type, payload) = netlink::messages(&message).next().unwrap();
+        assert_eq!(message_type, RTM_NEWADDR);
+
+        let (index, interface, address) = parse_message(payload).unwrap();
+
+        assert_eq!(index, 7);
+        assert_eq!(interface.as_deref(), Some("tun0"));
+        assert_eq!(address.address, "10.8.0.1".parse::<IpAddr>().unwrap());
+        assert_eq!(address.prefix_len, 16);
+    }
+
+    fn ifaddrmsg(index: u32, prefix_len: u8, attributes: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut payload = vec![libc::AF_INET as u8, prefix_len, 0, 0];
+        payload.extend(index.to_ne_bytes());
+        for (attribute, value) in attributes {
+            payload.extend(netlink::attribute(*attribute, value));
+        }
+
+        payload
+    }
+
+    #[test]
+    fn test_from_dump() {
+        let mut data = netlink::message(
+            RTM_NEWADDR,
+            0,
+            &ifaddrmsg(
+                2,
+                24,
+                &[(IFA_LOCAL, &[192, 168, 1, 14]), (IFA_LABEL, b"wlan0\0")],
+            ),
+        );
+        data.extend(netlink::message(
+            RTM_NEWADDR,
+            0,
+            &ifaddrmsg(
+                2,
+                24,
+                &[(IFA_LOCAL, &[192, 168, 1, 15]), (IFA_LABEL, b"wlan0:1\0")],
+            ),
+        ));
+        // Not an address
+        data.extend(netlink::message(
+            RTM_DELADDR,
+            0,
+            &ifaddrmsg(3, 8, &[(IFA_LOCAL, &[10, 0, 0, 1]), (IFA_LABEL, b"eth0\0")]),
+        ));
+        // Neither a label nor an existing interface
+        data.extend(netlink::message(
+            RTM_NEWADDR,
+            0,
+            &ifaddrmsg(u32::MAX, 8, &[(IFA_LOCAL, &[10, 0, 0, 2])]),
+        ));
+
+        let messages = netlink::messages(&data)
+            .map(|(message_type, payload)| (message_type, payload.to_vec()))
+            .collect();
+        let addresses = from_dump(messages);
+
+        assert_eq!(addresses.len(), 1);
+        let wlan0: Vec<IpAddr> = addresses["wlan0"].iter().map(|a| a.address).collect();
+        assert_eq!(
+            wlan0,
+            vec![
+                "192.168.1.14".parse::<IpAddr>().unwrap(),
+                "192.168.1.15".parse().unwrap()
+            ]
+        );
+        assert_eq!(addresses["wlan0"][0].prefix_len, 24);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    helper::read_value,
+    modules::process::PROC,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs, path::Path};
+
+/// Utilisation of the connection tracking table of netfilter.
+///
+/// When the table is full, new connections are dropped, which shows up as
+/// `drop` and `early_drop` in the statistics.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Conntrack {
+    /// Connections currently tracked.
+    count: u64,
+    /// Size of the table.
+    max: Option<u64>,
+    /// Counters of `/proc/net/stat/nf_conntrack`, summed over all CPUs.
+    statistics: HashMap<String, u64>,
+}
+
+impl Parser for Conntrack {
+    /// Fails with [DataError::FileNotFound] if the `nf_conntrack` module is
+    /// not loaded.
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        let netfilter = format!("{}sys/net/netfilter/", PROC);
+        let count = read_value(format!("{}nf_conntrack_count", netfilter))
+            .ok_or(DataError::FileNotFound)?;
+
+        let stat = format!("{}net/stat/nf_conntrack", PROC);
+        let statistics = if Path::new(&stat).exists() {
+            let content = fs::read_to_string(stat).map_err(|_| DataError::FileNotFound)?;
+            parse_statistics(&content).ok_or(DataError::Parsing)?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Conntrack {
+            count,
+            max: read_value(format!("{}nf_conntrack_max", netfilter)),
+            statistics,
+        })
+    }
+}
+
+/// Parse a table with a header of names and one line of hexadecimal values
+/// per CPU.
+fn parse_statistics(content: &str) -> Option<HashMap<String, u64>> {
+    let mut lines = content.lines();
+    let names: Vec<&str> = lines.next()?.split_whitespace().collect();
+
+    let mut statistics: HashMap<String, u64> = HashMap::new();
+    for (cpu, line) in lines.enumerate() {
+        let values: Vec<u64> = line
+            .split_whitespace()
+            .map(|value| u64::from_str_radix(value, 16).ok())
+            .collect::<Option<_>>()?;
+
+        if values.len() != names.len() {
+            return None;
+        }
+
+        for (name, value) in names.iter().zip(values) {
+            // The number of entries is global, repeated on every line
+            if *name == "entries" && cpu > 0 {
+                continue;
+            }
+            *statistics.entry(name.to_string()).or_default() += value;
+        }
+    }
+
+    Some(statistics)
+}
+
+impl Conntrack {
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    pub fn statistics(&self) -> &HashMap<String, u64> {
+        &self.statistics
+    }
+
+    /// Fill level of the table in percent.
+    pub fn utilisation(&self) -> Option<f64> {
+        self.max
+            .filter(|max| *max != 0)
+            .map(|max| self.count as f64 * 100. / max as f64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let conntrack = Conntrack::parse().unwrap();
+
+        assert_eq!(conntrack.count, 61234);
+        assert_eq!(conntrack.max, Some(65536));
+        assert_eq!(conntrack.utilisation().map(|u| u.round()), Some(93.));
+
+        assert_eq!(conntrack.statistics["entries"], 0xef32);
+        assert_eq!(conntrack.statistics["drop"], 5);
+        assert_eq!(conntrack.statistics["search_restart"], 5);
+        assert_eq!(conntrack.statistics["ignore"], 0x1a2b + 0x19f0);
+    }
+}
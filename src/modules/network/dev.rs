@@ -0,0 +1,126 @@
+use super::Statistics;
+use crate::{
+    modules::process::PROC,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs};
+
+/// Rust representation of `/proc/net/dev`.
+///
+/// Unlike `/sys/class/net`, which lists the interfaces of the network
+/// namespace sysfs was mounted in, this lists the interfaces of the namespace
+/// of a process, refer to [NetDev::from_pid].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NetDev {
+    interfaces: HashMap<String, Statistics>,
+}
+
+impl Parser for NetDev {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        NetDev::read(&format!("{}net/dev", PROC))
+    }
+}
+
+impl NetDev {
+    /// Read the interfaces of the network namespace of a process.
+    pub fn from_pid(pid: u32) -> Result<NetDev, DataError> {
+        NetDev::read(&format!("{}{}/net/dev", PROC, pid)).map_err(|e| match e {
+            DataError::FileNotFound => DataError::ProcessExited,
+            e => e,
+        })
+    }
+
+    fn read(path: &str) -> Result<NetDev, DataError> {
+        let content = fs::read_to_string(path).map_err(|_| DataError::FileNotFound)?;
+
+        let interfaces = content
+            .lines()
+            // Two lines of headers
+            .skip(2)
+            .map(|line| parse_line(line).ok_or(DataError::Parsing))
+            .collect::<Result<_, _>>()?;
+
+        Ok(NetDev { interfaces })
+    }
+
+    pub fn interfaces(&self) -> &HashMap<String, Statistics> {
+        &self.interfaces
+    }
+
+    pub(crate) fn into_interfaces(self) -> HashMap<String, Statistics> {
+        self.interfaces
+    }
+}
+
+/// Parse a line such as `  eth0: 2314552 4120 0 3 ...`.
+///
+/// Only the counters present in the file are set: `drop` of the receive side
+/// is the sum of `rx_dropped` and `rx_missed_errors`, and `errs` the sum of
+/// all errors.
+fn parse_line(line: &str) -> Option<(String, Statistics)> {
+    let (name, counters) = line.split_once(':')?;
+    let counters: Vec<u64> = counters
+        .split_whitespace()
+        .map(|counter| counter.parse().ok())
+        .collect::<Option<_>>()?;
+
+    if counters.len() != 16 {
+        return None;
+    }
+
+    Some((
+        name.trim().to_string(),
+        Statistics {
+            rx_bytes: counters[0],
+            rx_packets: counters[1],
+            rx_errors: counters[2],
+            rx_dropped: counters[3],
+            rx_fifo_errors: counters[4],
+            rx_frame_errors: counters[5],
+            rx_compressed: counters[6],
+            multicast: counters[7],
+            tx_bytes: counters[8],
+            tx_packets: counters[9],
+            tx_errors: counters[10],
+            tx_dropped: counters[11],
+            tx_fifo_errors: counters[12],
+            collisions: counters[13],
+            tx_carrier_errors: counters[14],
+            tx_compressed: counters[15],
+            ..Default::default()
+        },
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let dev = NetDev::parse().unwrap();
+
+        assert_eq!(dev.interfaces.len(), 2);
+        assert_eq!(dev.interfaces["wlan0"].rx_bytes, 667784075);
+        assert_eq!(dev.interfaces["wlan0"].rx_dropped, 1822);
+        assert_eq!(dev.interfaces["lo"].tx_packets, 12842);
+    }
+
+    #[test]
+    fn test_from_pid() {
+        let dev = NetDev::from_pid(4242).unwrap();
+
+        assert_eq!(dev.interfaces["eth0"].multicast, 12);
+        assert_eq!(dev.interfaces["eth0"].tx_bytes, 913402);
+        assert!(!dev.interfaces.contains_key("wlan0"));
+
+        assert!(matches!(
+            NetDev::from_pid(999999),
+            Err(DataError::ProcessExited)
+        ));
+    }
+}
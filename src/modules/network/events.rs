@@ -0,0 +1,354 @@
+use super::{
+    address::{self, InterfaceAddress, RTM_DELADDR, RTM_NEWADDR},
+    Network, NetworkDevice,
+};
+use crate::{
+    modules::netlink,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    os::fd::OwnedFd,
+};
+
+/// Multicast groups of `include/uapi/linux/rtnetlink.h`.
+const RTMGRP_LINK: u32 = 0x1;
+const RTMGRP_IPV4_IFADDR: u32 = 0x10;
+const RTMGRP_IPV6_IFADDR: u32 = 0x100;
+
+/// Message types and attributes of `include/uapi/linux/if_link.h`.
+const RTM_NEWLINK: u16 = 16;
+const RTM_DELLINK: u16 = 17;
+const IFLA_IFNAME: u16 = 3;
+
+/// Size of `struct ifinfomsg`.
+const IFINFOMSG_LEN: usize = 16;
+
+/// A change of a network interface, along with the interface after the change.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum LinkEvent {
+    /// An interface was created, or moved into the network namespace.
+    Added { name: String, device: NetworkDevice },
+    /// The state of an interface changed, e.g. it went up or down, gained or
+    /// lost its carrier, or was renamed.
+    ///
+    /// The kernel also notifies changes which are not part of
+    /// [NetworkDevice], so the device may look the same as before.
+    Changed { name: String, device: NetworkDevice },
+    /// An interface was removed, along with its last known state.
+    Removed { name: String, device: NetworkDevice },
+    AddressAdded {
+        name: String,
+        address: InterfaceAddress,
+        device: NetworkDevice,
+    },
+    AddressRemoved {
+        name: String,
+        address: InterfaceAddress,
+        device: NetworkDevice,
+    },
+}
+
+/// Stream of changes of the network interfaces, from a rtnetlink
+/// subscription.
+///
+/// Interfaces are read from sysfs when they change, so the stream shows the
+/// network namespace of the caller. Iterating blocks until the next event.
+pub struct LinkEvents {
+    socket: OwnedFd,
+    /// The interfaces, kept up to date with the events.
+    devices: HashMap<String, NetworkDevice>,
+    pending: VecDeque<LinkEvent>,
+}
+
+impl LinkEvents {
+    pub fn new() -> Result<LinkEvents, DataError> {
+        // Subscribe before the snapshot so that no change is missed in between
+        let socket = netlink::socket(
+            libc::NETLINK_ROUTE,
+            RTMGRP_LINK | RTMGRP_IPV4_IFADDR | RTMGRP_IPV6_IFADDR,
+        )
+        .map_err(|e| match e.raw_os_error() {
+            Some(libc::EPERM) | Some(libc::EACCES) => DataError::PermissionDenied,
+            _ => DataError::FileNotFound,
+        })?;
+
+        Ok(LinkEvents {
+            socket,
+            devices: Network::parse()?.devices,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// The interfaces as of the last event returned.
+    pub fn devices(&self) -> &HashMap<String, NetworkDevice> {
+        &self.devices
+    }
+
+    /// Wait for the next batch of events and queue them.
+    fn fill(&mut self) -> Result<(), DataError> {
+        let mut buffer = vec![0u8; 32 * 1024];
+
+        let len = match netlink::recv(&self.socket, &mut buffer) {
+            Ok(len) => len,
+            // Events were dropped because we were too slow
+            Err(e) if e.raw_os_error() == Some(libc::ENOBUFS) => {
+                log::warn!("rtnetlink events were dropped, reading the interfaces again");
+
+                let current = Network::parse()?.devices;
+                self.pending.extend(resync(&self.devices, &current));
+                self.devices = current;
+
+                return Ok(());
+            }
+            Err(_) => return Err(DataError::FileNotFound),
+        };
+
+        for (message_type, payload) in netlink::messages(&buffer[..len]) {
+            self.pending
+                .extend(apply(&mut self.devices, message_type, payload));
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for LinkEvents {
+    type Item = Result<LinkEvent, DataError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            if let Err(e) = self.fill() {
+                return Some(Err(e));
+            }
+        }
+
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Name of the interface with the given index.
+fn find(devices: &HashMap<String, NetworkDevice>, index: u32) -> Option<String> {
+    devices
+        .iter()
+        .find(|(_, device)| device.ifindex == Some(index))
+        .map(|(name, _)| name.clone())
+}
+
+/// Decode a `RTM_NEWLINK` or `RTM_DELLINK` message into the index and the
+/// name of the interface.
+fn parse_link(payload: &[u8]) -> Option<(u32, String)> {
+    let index = netlink::u32_at(payload.get(..IFINFOMSG_LEN)?, 4)?;
+
+    let name = netlink::attributes(&payload[IFINFOMSG_LEN..])
+        .find(|(attribute, _)| *attribute == IFLA_IFNAME)
+        .map(|(_, value)| netlink::string(value))?;
+
+    Some((index, name))
+}
+
+/// Update the interfaces with a message and translate it into an event.
+fn apply(
+    devices: &mut HashMap<String, NetworkDevice>,
+    message_type: u16,
+    payload: &[u8],
+) -> Option<LinkEvent> {
+    match message_type {
+        RTM_NEWLINK | RTM_DELLINK => {
+            let (index, name) = parse_link(payload)?;
+
+            // Looked up by index, in case the interface was renamed
+            let previous = find(devices, index)
+                .or_else(|| devices.contains_key(&name).then(|| name.clone()))
+                .and_then(|previous| devices.remove(&previous));
+
+            if message_type == RTM_DELLINK {
+                return previous.map(|device| LinkEvent::Removed { name, device });
+            }
+
+            let mut device = NetworkDevice::from_name(&name);
+            device.ifindex.get_or_insert(index);
+            // Addresses have their own events
+            device.addresses = previous
+                .as_ref()
+                .map(|previous| previous.addresses.clone())
+                .unwrap_or_default();
+
+            devices.insert(name.clone(), device.clone());
+
+            Some(match previous {
+                Some(_) => LinkEvent::Changed { name, device },
+                None => LinkEvent::Added { name, device },
+            })
+        }
+        RTM_NEWADDR | RTM_DELADDR => {
+            let (index, label, address) = address::parse_message(payload)?;
+            let name = find(devices, index).or(label)?;
+            let device = devices.get_mut(&name)?;
+
+            if message_type == RTM_NEWADDR {
+                if !device.addresses.contains(&address) {
+                    device.addresses.push(address.clone());
+                }
+
+                Some(LinkEvent::AddressAdded {
+                    name,
+                    address,
+                    device: device.clone(),
+                })
+            } else {
+                device.addresses.retain(|other| *other != address);
+
+                Some(LinkEvent::AddressRemoved {
+                    name,
+                    address,
+                    device: device.clone(),
+                })
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Translate the differences between two snapshots of the interfaces into
+/// events, when events were lost.
+fn resync(
+    before: &HashMap<String, NetworkDevice>,
+    after: &HashMap<String, NetworkDevice>,
+) -> Vec<LinkEvent> {
+    let removed = before
+        .iter()
+        .filter(|(name, _)| !after.contains_key(*name))
+        .map(|(name, device)| LinkEvent::Removed {
+            name: name.clone(),
+            device: device.clone(),
+        });
+
+    let added_or_changed = after.iter().filter_map(|(name, device)| {
+        let (name, device) = (name.clone(), device.clone());
+
+        match before.get(&name) {
+            None => Some(LinkEvent::Added { name, device }),
+            Some(previous)
+                if (
+                    previous.operstate,
+                    previous.carrier,
+                    previous.mtu,
+                    &previous.addresses,
+                ) != (
+                    device.operstate,
+                    device.carrier,
+                    device.mtu,
+                    &device.addresses,
+                ) =>
+            {
+                Some(LinkEvent::Changed { name, device })
+            }
+            Some(_) => None,
+        }
+    });
+
+    removed.chain(added_or_changed).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::modules::network::OperState;
+
+    fn link(message_type: u16, index: u32, name: &str) -> Vec<u8> {
+        let mut payload = vec![0u8; 4];
+        payload.extend(index.to_ne_bytes());
+        payload.extend([0; 8]);
+        payload.extend(netlink::attribute(
+            IFLA_IFNAME,
+            format!("{}\0", name).as_bytes(),
+        ));
+
+        netlink::message(message_type, 0, &payload)
+    }
+
+    fn address(message_type: u16, index: u32, label: &str) -> Vec<u8> {
+        let mut payload = vec![libc::AF_INET as u8, 8, 0, 0];
+        payload.extend(index.to_ne_bytes());
+        payload.extend(netlink::attribute(address::IFA_LOCAL, &[10, 0, 0, 5]));
+        payload.extend(netlink::attribute(
+            address::IFA_LABEL,
+            format!("{}\0", label).as_bytes(),
+        ));
+
+        netlink::message(message_type, 0, &payload)
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut devices = Network::parse().unwrap().devices;
+
+        let mut data = link(RTM_NEWLINK, 3, "wlan0");
+        data.extend(address(RTM_NEWADDR, 3, "wlan0"));
+        data.extend(address(RTM_DELADDR, 3, "wlan0"));
+        data.extend(link(RTM_NEWLINK, 9, "veth0"));
+        data.extend(link(RTM_NEWLINK, 9, "veth1"));
+        data.extend(link(RTM_DELLINK, 9, "veth1"));
+
+        let events: Vec<LinkEvent> = netlink::messages(&data)
+            .filter_map(|(message_type, payload)| apply(&mut devices, message_type, payload))
+            .collect();
+
+        assert_eq!(events.len(), 6);
+
+        let LinkEvent::Changed { name, device } = &events[0] else {
+            panic!("{:?}", events[0]);
+        };
+        assert_eq!(name, "wlan0");
+        assert_eq!(device.operstate, Some(OperState::Up));
+        assert_eq!(device.addresses.len(), 3);
+
+        let LinkEvent::AddressAdded {
+            address, device, ..
+        } = &events[1]
+        else {
+            panic!("{:?}", events[1]);
+        };
+        assert_eq!(
+            address.address(),
+            "10.0.0.5".parse::<std::net::IpAddr>().unwrap()
+        );
+        assert_eq!(device.addresses.len(), 4);
+
+        assert!(
+            matches!(&events[2], LinkEvent::AddressRemoved { device, .. } if device.addresses.len() == 3)
+        );
+
+        let LinkEvent::Added { name, device } = &events[3] else {
+            panic!("{:?}", events[3]);
+        };
+        assert_eq!(name, "veth0");
+        assert_eq!(device.ifindex, Some(9));
+
+        assert!(matches!(&events[4], LinkEvent::Changed { name, .. } if name == "veth1"));
+        assert!(matches!(&events[5], LinkEvent::Removed { name, .. } if name == "veth1"));
+        assert_eq!(devices.len(), 1);
+    }
+
+    #[test]
+    fn test_resync() {
+        let before = Network::parse().unwrap().devices;
+        let mut after = before.clone();
+
+        assert!(resync(&before, &after).is_empty());
+
+        after.get_mut("wlan0").unwrap().carrier = Some(false);
+        assert!(matches!(
+            &resync(&before, &after)[..],
+            [LinkEvent::Changed { .. }]
+        ));
+
+        after.clear();
+        assert!(matches!(
+            &resync(&before, &after)[..],
+            [LinkEvent::Removed { .. }]
+        ));
+    }
+}
@@ -0,0 +1,545 @@
+use crate::{
+    helper::read_value,
+    parser::{DataError, Parser},
+    timestamp,
+};
+use address::InterfaceAddress;
+use conntrack::Conntrack;
+use dev::NetDev;
+use neighbour::{ArpEntry, ArpTable};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{self, DirEntry},
+    path::Path,
+    str::FromStr,
+};
+use topology::Topology;
+
+pub mod address;
+pub mod conntrack;
+pub mod dev;
+pub mod events;
+pub mod neighbour;
+pub mod rates;
+pub mod route;
+pub mod snmp;
+pub mod socket;
+pub mod topology;
+
+#[cfg(test)]
+const DEVICE_DIR: &str = "./mock/sysclassnet/";
+#[cfg(not(test))]
+const DEVICE_DIR: &str = "/sys/class/net/";
+
+/// Counters of `/sys/class/net/<if>/statistics`, refer to
+/// `Documentation/ABI/testing/sysfs-class-net-statistics`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Statistics {
+    rx_bytes: u64,
+    rx_packets: u64,
+    rx_errors: u64,
+    rx_dropped: u64,
+    rx_fifo_errors: u64,
+    rx_frame_errors: u64,
+    rx_compressed: u64,
+    rx_crc_errors: u64,
+    rx_length_errors: u64,
+    rx_missed_errors: u64,
+    rx_over_errors: u64,
+    /// Packets dropped because no protocol handled them.
+    rx_nohandler: u64,
+    multicast: u64,
+    collisions: u64,
+    tx_bytes: u64,
+    tx_packets: u64,
+    tx_errors: u64,
+    tx_dropped: u64,
+    tx_fifo_errors: u64,
+    tx_carrier_errors: u64,
+    tx_compressed: u64,
+    tx_aborted_errors: u64,
+    tx_heartbeat_errors: u64,
+    tx_window_errors: u64,
+}
+
+impl Statistics {
+    /// Read the counters, those missing on older kernels are left at 0.
+    fn parse(dir: &Path) -> Statistics {
+        let counter = |name: &str| read_value(dir.join(name)).unwrap_or_default();
+
+        Statistics {
+            rx_bytes: counter("rx_bytes"),
+            rx_packets: counter("rx_packets"),
+            rx_errors: counter("rx_errors"),
+            rx_dropped: counter("rx_dropped"),
+            rx_fifo_errors: counter("rx_fifo_errors"),
+            rx_frame_errors: counter("rx_frame_errors"),
+            rx_compressed: counter("rx_compressed"),
+            rx_crc_errors: counter("rx_crc_errors"),
+            rx_length_errors: counter("rx_length_errors"),
+            rx_missed_errors: counter("rx_missed_errors"),
+            rx_over_errors: counter("rx_over_errors"),
+            rx_nohandler: counter("rx_nohandler"),
+            multicast: counter("multicast"),
+            collisions: counter("collisions"),
+            tx_bytes: counter("tx_bytes"),
+            tx_packets: counter("tx_packets"),
+            tx_errors: counter("tx_errors"),
+            tx_dropped: counter("tx_dropped"),
+            tx_fifo_errors: counter("tx_fifo_errors"),
+            tx_carrier_errors: counter("tx_carrier_errors"),
+            tx_compressed: counter("tx_compressed"),
+            tx_aborted_errors: counter("tx_aborted_errors"),
+            tx_heartbeat_errors: counter("tx_heartbeat_errors"),
+            tx_window_errors: counter("tx_window_errors"),
+        }
+    }
+
+    pub fn rx_bytes(&self) -> u64 {
+        self.rx_bytes
+    }
+
+    pub fn rx_packets(&self) -> u64 {
+        self.rx_packets
+    }
+
+    pub fn rx_errors(&self) -> u64 {
+        self.rx_errors
+    }
+
+    pub fn rx_dropped(&self) -> u64 {
+        self.rx_dropped
+    }
+
+    pub fn rx_fifo_errors(&self) -> u64 {
+        self.rx_fifo_errors
+    }
+
+    pub fn rx_frame_errors(&self) -> u64 {
+        self.rx_frame_errors
+    }
+
+    pub fn rx_compressed(&self) -> u64 {
+        self.rx_compressed
+    }
+
+    pub fn rx_crc_errors(&self) -> u64 {
+        self.rx_crc_errors
+    }
+
+    pub fn rx_length_errors(&self) -> u64 {
+        self.rx_length_errors
+    }
+
+    pub fn rx_missed_errors(&self) -> u64 {
+        self.rx_missed_errors
+    }
+
+    pub fn rx_over_errors(&self) -> u64 {
+        self.rx_over_errors
+    }
+
+    pub fn rx_nohandler(&self) -> u64 {
+        self.rx_nohandler
+    }
+
+    pub fn multicast(&self) -> u64 {
+        self.multicast
+    }
+
+    pub fn collisions(&self) -> u64 {
+        self.collisions
+    }
+
+    pub fn tx_bytes(&self) -> u64 {
+        self.tx_bytes
+    }
+
+    pub fn tx_packets(&self) -> u64 {
+        self.tx_packets
+    }
+
+    pub fn tx_errors(&self) -> u64 {
+        self.tx_errors
+    }
+
+    pub fn tx_dropped(&self) -> u64 {
+        self.tx_dropped
+    }
+
+    pub fn tx_fifo_errors(&self) -> u64 {
+        self.tx_fifo_errors
+    }
+
+    pub fn tx_carrier_errors(&self) -> u64 {
+        self.tx_carrier_errors
+    }
+
+    pub fn tx_compressed(&self) -> u64 {
+        self.tx_compressed
+    }
+
+    pub fn tx_aborted_errors(&self) -> u64 {
+        self.tx_aborted_errors
+    }
+
+    pub fn tx_heartbeat_errors(&self) -> u64 {
+        self.tx_heartbeat_errors
+    }
+
+    pub fn tx_window_errors(&self) -> u64 {
+        self.tx_window_errors
+    }
+}
+
+/// Operational state of an interface, refer to RFC 2863.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperState {
+    Unknown,
+    NotPresent,
+    Down,
+    LowerLayerDown,
+    Testing,
+    Dormant,
+    Up,
+}
+
+impl FromStr for OperState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unknown" => Ok(OperState::Unknown),
+            "notpresent" => Ok(OperState::NotPresent),
+            "down" => Ok(OperState::Down),
+            "lowerlayerdown" => Ok(OperState::LowerLayerDown),
+            "testing" => Ok(OperState::Testing),
+            "dormant" => Ok(OperState::Dormant),
+            "up" => Ok(OperState::Up),
+            s => Err(format!("Bad operstate: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Duplex {
+    Full,
+    Half,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkDevice {
+    total_rx: Option<u64>,
+    total_tx: Option<u64>,
+    statistics: Statistics,
+    operstate: Option<OperState>,
+    /// Whether the physical link is up, `None` if the interface is down.
+    carrier: Option<bool>,
+    mtu: Option<u32>,
+    /// Hardware address.
+    address: Option<String>,
+    /// Link speed in Mbit/s, if known.
+    speed: Option<u32>,
+    duplex: Option<Duplex>,
+    /// Hardware type, one of the `ARPHRD_*` constants of `include/uapi/linux/if_arp.h`.
+    link_type: Option<u16>,
+    ifindex: Option<u32>,
+    tx_queue_len: Option<u32>,
+    /// Not backed by a device, e.g. loopback, bridges or veth pairs.
+    is_virtual: bool,
+    is_bridge: bool,
+    is_bond: bool,
+    is_vlan: bool,
+    /// IPv4 and IPv6 addresses.
+    addresses: Vec<InterfaceAddress>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Network {
+    /// Time the interfaces were read at, as a Unix timestamp in milliseconds.
+    timestamp: u128,
+    devices: HashMap<String, NetworkDevice>,
+    /// IPv4 neighbours.
+    arp: Vec<ArpEntry>,
+    /// `None` if connection tracking is not enabled.
+    conntrack: Option<Conntrack>,
+    /// Bonds, bridges, vlans and veth pairs.
+    topology: Topology,
+}
+
+impl Parser for Network {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        let mut hash: HashMap<String, NetworkDevice> = HashMap::new();
+        let mut addresses = address::addresses();
+
+        if let Ok(dir) = fs::read_dir(DEVICE_DIR) {
+            dir.for_each(|dir| {
+                let dir = dir.unwrap();
+                let x = dir.file_name().to_str().map(|s| s.to_string());
+                let mut data = NetworkDevice::parse(dir);
+                if let Some(x) = &x {
+                    data.addresses = addresses.remove(x).unwrap_or_default();
+                }
+
+                if let Some(x) = x {
+                    hash.insert(x, data)
+                } else {
+                    None
+                };
+            })
+        };
+
+        Ok(Network {
+            timestamp: timestamp!(),
+            devices: hash,
+            arp: ArpTable::parse()
+                .map(|table| table.entries().to_vec())
+                .unwrap_or_default(),
+            conntrack: Conntrack::parse().ok(),
+            topology: Topology::parse().unwrap_or_default(),
+        })
+    }
+}
+
+impl Network {
+    /// Read the interfaces of the network namespace of a process from
+    /// `/proc/[pid]/net/dev`.
+    ///
+    /// Only the statistics and neighbours are known, the attributes of the
+    /// interfaces, conntrack and the topology are left empty as sysfs and sysctl may not
+    /// show this namespace.
+    pub fn from_pid(pid: u32) -> Result<Network, DataError> {
+        let devices = NetDev::from_pid(pid)?
+            .into_interfaces()
+            .into_iter()
+            .map(|(name, statistics)| (name, NetworkDevice::from_statistics(statistics)))
+            .collect();
+
+        Ok(Network {
+            timestamp: timestamp!(),
+            devices,
+            arp: ArpTable::from_pid(pid)
+                .map(|table| table.entries().to_vec())
+                .unwrap_or_default(),
+            conntrack: None,
+            topology: Topology::default(),
+        })
+    }
+
+    pub fn timestamp(&self) -> u128 {
+        self.timestamp
+    }
+
+    pub fn devices(&self) -> &HashMap<String, NetworkDevice> {
+        &self.devices
+    }
+
+    pub fn arp(&self) -> &[ArpEntry] {
+        &self.arp
+    }
+
+    pub fn conntrack(&self) -> Option<&Conntrack> {
+        self.conntrack.as_ref()
+    }
+
+    pub fn topology(&self) -> &Topology {
+        &self.topology
+    }
+}
+
+impl NetworkDevice {
+    fn parse(dir: DirEntry) -> NetworkDevice {
+        NetworkDevice::from_path(&dir.path())
+    }
+
+    /// Read the interface `name` from sysfs.
+    fn from_name(name: &str) -> NetworkDevice {
+        NetworkDevice::from_path(&Path::new(DEVICE_DIR).join(name))
+    }
+
+    fn from_path(path: &Path) -> NetworkDevice {
+        let rx = read_value(path.join("statistics/rx_bytes"));
+        let tx = read_value(path.join("statistics/tx_bytes"));
+
+        let devtype = fs::read_to_string(path.join("uevent"))
+            .ok()
+            .and_then(|uevent| {
+                uevent
+                    .lines()
+                    .find_map(|line| line.strip_prefix("DEVTYPE=").map(|s| s.to_string()))
+            });
+
+        NetworkDevice {
+            total_rx: rx,
+            total_tx: tx,
+            statistics: Statistics::parse(&path.join("statistics")),
+            operstate: read_value(path.join("operstate")),
+            carrier: read_value::<u8>(&path.join("carrier")).map(|carrier| carrier == 1),
+            mtu: read_value(path.join("mtu")),
+            address: read_value(path.join("address")),
+            // -1 when unknown
+            speed: read_value::<i64>(&path.join("speed")).and_then(|speed| speed.try_into().ok()),
+            duplex: match read_value::<String>(&path.join("duplex")).as_deref() {
+                Some("full") => Some(Duplex::Full),
+                Some("half") => Some(Duplex::Half),
+                _ => None,
+            },
+            link_type: read_value(path.join("type")),
+            ifindex: read_value(path.join("ifindex")),
+            tx_queue_len: read_value(path.join("tx_queue_len")),
+            is_virtual: fs::symlink_metadata(path.join("device")).is_err(),
+            is_bridge: path.join("bridge").is_dir(),
+            is_bond: path.join("bonding").is_dir(),
+            is_vlan: devtype.as_deref() == Some("vlan"),
+            addresses: vec![],
+        }
+    }
+
+    fn from_statistics(statistics: Statistics) -> NetworkDevice {
+        NetworkDevice {
+            total_rx: Some(statistics.rx_bytes),
+            total_tx: Some(statistics.tx_bytes),
+            statistics,
+            operstate: None,
+            carrier: None,
+            mtu: None,
+            address: None,
+            speed: None,
+            duplex: None,
+            link_type: None,
+            ifindex: None,
+            tx_queue_len: None,
+            is_virtual: false,
+            is_bridge: false,
+            is_bond: false,
+            is_vlan: false,
+            addresses: vec![],
+        }
+    }
+
+    pub fn total_rx(&self) -> Option<u64> {
+        self.total_rx
+    }
+
+    pub fn total_tx(&self) -> Option<u64> {
+        self.total_tx
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    pub fn operstate(&self) -> Option<OperState> {
+        self.operstate
+    }
+
+    pub fn carrier(&self) -> Option<bool> {
+        self.carrier
+    }
+
+    pub fn mtu(&self) -> Option<u32> {
+        self.mtu
+    }
+
+    pub fn address(&self) -> Option<&str> {
+        self.address.as_deref()
+    }
+
+    pub fn speed(&self) -> Option<u32> {
+        self.speed
+    }
+
+    pub fn duplex(&self) -> Option<Duplex> {
+        self.duplex
+    }
+
+    pub fn link_type(&self) -> Option<u16> {
+        self.link_type
+    }
+
+    pub fn ifindex(&self) -> Option<u32> {
+        self.ifindex
+    }
+
+    pub fn tx_queue_len(&self) -> Option<u32> {
+        self.tx_queue_len
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        self.is_virtual
+    }
+
+    pub fn is_bridge(&self) -> bool {
+        self.is_bridge
+    }
+
+    pub fn is_bond(&self) -> bool {
+        self.is_bond
+    }
+
+    pub fn is_vlan(&self) -> bool {
+        self.is_vlan
+    }
+
+    pub fn addresses(&self) -> &[InterfaceAddress] {
+        &self.addresses
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_network_parse() {
+        let res = Network::parse().unwrap();
+
+        assert_eq!(res.devices.len(), 1);
+        assert_eq!(res.arp.len(), 3);
+        assert_eq!(res.conntrack.unwrap().count(), 61234);
+    }
+
+    #[test]
+    fn test_network_device_parse() {
+        let dir = fs::read_dir(DEVICE_DIR)
+            .expect("1")
+            .next()
+            .expect("2")
+            .expect("3");
+
+        let res = NetworkDevice::parse(dir);
+
+        assert_ne!(res.total_rx, None);
+        assert_ne!(res.total_tx, None);
+    }
+
+    #[test]
+    fn test_network_from_pid() {
+        let res = Network::from_pid(4242).unwrap();
+
+        assert_eq!(res.devices.len(), 2);
+        assert_eq!(res.devices["eth0"].total_rx, Some(2314552));
+        assert_eq!(res.devices["eth0"].operstate, None);
+    }
+
+    #[test]
+    fn test_network_device_attributes() {
+        let res = Network::parse().unwrap();
+        let wlan = &res.devices["wlan0"];
+
+        assert_eq!(wlan.statistics().rx_bytes(), 667784075);
+        assert_eq!(wlan.statistics().rx_dropped(), 1822);
+        assert_eq!(wlan.operstate, Some(OperState::Up));
+        assert_eq!(wlan.carrier, Some(true));
+        assert_eq!(wlan.mtu, Some(1500));
+        assert_eq!(wlan.address(), Some("3c:58:c2:9a:1f:07"));
+        assert_eq!(wlan.speed, None);
+        assert_eq!(wlan.ifindex, Some(3));
+        assert!(!wlan.is_virtual && !wlan.is_bridge && !wlan.is_vlan);
+        assert_eq!(wlan.addresses.len(), 3);
+    }
+}
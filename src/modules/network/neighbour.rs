@@ -0,0 +1,130 @@
+use crate::{
+    modules::process::PROC,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, net::Ipv4Addr};
+
+/// The entry is resolved.
+const ATF_COM: u32 = 0x02;
+/// The entry was added manually and never expires.
+const ATF_PERM: u32 = 0x04;
+
+/// A line of `/proc/net/arp`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ArpEntry {
+    ip: Ipv4Addr,
+    /// Hardware type, one of the `ARPHRD_*` constants of `include/uapi/linux/if_arp.h`.
+    hw_type: u16,
+    /// `ATF_*` flags of `include/uapi/linux/if_arp.h`.
+    flags: u32,
+    /// Hardware address, all zeroes until resolved.
+    hw_address: String,
+    device: String,
+}
+
+impl ArpEntry {
+    fn parse(line: &str) -> Option<ArpEntry> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 6 {
+            return None;
+        }
+
+        Some(ArpEntry {
+            ip: fields[0].parse().ok()?,
+            hw_type: u16::from_str_radix(fields[1].strip_prefix("0x")?, 16).ok()?,
+            flags: u32::from_str_radix(fields[2].strip_prefix("0x")?, 16).ok()?,
+            hw_address: fields[3].to_string(),
+            device: fields[5].to_string(),
+        })
+    }
+
+    pub fn ip(&self) -> Ipv4Addr {
+        self.ip
+    }
+
+    pub fn hw_type(&self) -> u16 {
+        self.hw_type
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    pub fn hw_address(&self) -> &str {
+        &self.hw_address
+    }
+
+    pub fn device(&self) -> &str {
+        &self.device
+    }
+
+    /// Whether the hardware address is known.
+    pub fn is_complete(&self) -> bool {
+        self.flags & ATF_COM != 0
+    }
+
+    pub fn is_permanent(&self) -> bool {
+        self.flags & ATF_PERM != 0
+    }
+}
+
+/// The IPv4 neighbour table of a network namespace.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ArpTable {
+    entries: Vec<ArpEntry>,
+}
+
+impl Parser for ArpTable {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        ArpTable::read(&format!("{}net/arp", PROC))
+    }
+}
+
+impl ArpTable {
+    /// Read the neighbours of the network namespace of a process.
+    pub fn from_pid(pid: u32) -> Result<ArpTable, DataError> {
+        ArpTable::read(&format!("{}{}/net/arp", PROC, pid))
+    }
+
+    fn read(path: &str) -> Result<ArpTable, DataError> {
+        let content = fs::read_to_string(path).map_err(|_| DataError::FileNotFound)?;
+
+        let entries = content
+            .lines()
+            // Header
+            .skip(1)
+            .map(|line| ArpEntry::parse(line).ok_or(DataError::Parsing))
+            .collect::<Result<_, _>>()?;
+
+        Ok(ArpTable { entries })
+    }
+
+    pub fn entries(&self) -> &[ArpEntry] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let arp = ArpTable::parse().unwrap();
+
+        assert_eq!(arp.entries.len(), 3);
+
+        let gateway = &arp.entries[0];
+        assert_eq!(gateway.ip, Ipv4Addr::new(192, 168, 1, 1));
+        assert_eq!(gateway.hw_address(), "a4:91:b1:2c:0e:33");
+        assert_eq!(gateway.device(), "wlan0");
+        assert!(gateway.is_complete() && !gateway.is_permanent());
+
+        assert!(!arp.entries[1].is_complete());
+        assert!(arp.entries[2].is_permanent());
+    }
+}
@@ -0,0 +1,207 @@
+use super::{Network, NetworkDevice};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Per second rates of an interface between two snapshots.
+///
+/// A rate is `None` when the counter went backwards for another reason than
+/// a 32-bit wrap, e.g. because the interface was recreated or its driver
+/// reloaded, as no meaningful rate can be computed in that case.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct InterfaceRates {
+    rx_bytes: Option<f64>,
+    tx_bytes: Option<f64>,
+    rx_packets: Option<f64>,
+    tx_packets: Option<f64>,
+    rx_errors: Option<f64>,
+    tx_errors: Option<f64>,
+    rx_dropped: Option<f64>,
+    tx_dropped: Option<f64>,
+    /// Whether the counters were reset between the snapshots.
+    reset: bool,
+}
+
+/// Link speed assumed when the interface does not report one, in Mbit/s.
+const DEFAULT_SPEED: u64 = 1000;
+/// Smallest Ethernet frame in bytes, which bounds the packets sent per byte.
+const MIN_FRAME: u64 = 64;
+
+/// Increase of a counter, accounting for counters of 32 bits that wrapped.
+///
+/// A counter that went backwards is taken as wrapped only if the increase
+/// through the wrap is at most `max`, the most the interface can do in the
+/// interval. Returns `None` if the counter was reset.
+fn delta(before: u64, after: u64, max: u64) -> Option<u64> {
+    const WRAP: u64 = 1 << 32;
+
+    if after >= before {
+        Some(after - before)
+    } else if before < WRAP && after < WRAP {
+        let wrapped = after + WRAP - before;
+        (wrapped <= max).then_some(wrapped)
+    } else {
+        None
+    }
+}
+
+impl InterfaceRates {
+    fn new(before: &NetworkDevice, after: &NetworkDevice, seconds: f64) -> InterfaceRates {
+        // A new index means the interface was recreated under the same name
+        if before.ifindex != after.ifindex {
+            return InterfaceRates {
+                reset: true,
+                ..Default::default()
+            };
+        }
+
+        let speed = after.speed.map(u64::from).unwrap_or(DEFAULT_SPEED);
+        let max_bytes = (speed as f64 * 1_000_000. / 8. * seconds) as u64;
+        let max_packets = max_bytes / MIN_FRAME;
+
+        let (before, after) = (&before.statistics, &after.statistics);
+        let mut reset = false;
+        let mut rate = |before: u64, after: u64, max: u64| {
+            let delta = delta(before, after, max);
+            reset |= delta.is_none();
+            delta.map(|delta| delta as f64 / seconds)
+        };
+
+        InterfaceRates {
+            rx_bytes: rate(before.rx_bytes, after.rx_bytes, max_bytes),
+            tx_bytes: rate(before.tx_bytes, after.tx_bytes, max_bytes),
+            rx_packets: rate(before.rx_packets, after.rx_packets, max_packets),
+            tx_packets: rate(before.tx_packets, after.tx_packets, max_packets),
+            rx_errors: rate(before.rx_errors, after.rx_errors, max_packets),
+            tx_errors: rate(before.tx_errors, after.tx_errors, max_packets),
+            rx_dropped: rate(before.rx_dropped, after.rx_dropped, max_packets),
+            tx_dropped: rate(before.tx_dropped, after.tx_dropped, max_packets),
+            reset,
+        }
+    }
+
+    /// Bytes received per second.
+    pub fn rx_bytes(&self) -> Option<f64> {
+        self.rx_bytes
+    }
+
+    /// Bytes sent per second.
+    pub fn tx_bytes(&self) -> Option<f64> {
+        self.tx_bytes
+    }
+
+    pub fn rx_packets(&self) -> Option<f64> {
+        self.rx_packets
+    }
+
+    pub fn tx_packets(&self) -> Option<f64> {
+        self.tx_packets
+    }
+
+    pub fn rx_errors(&self) -> Option<f64> {
+        self.rx_errors
+    }
+
+    pub fn tx_errors(&self) -> Option<f64> {
+        self.tx_errors
+    }
+
+    pub fn rx_dropped(&self) -> Option<f64> {
+        self.rx_dropped
+    }
+
+    pub fn tx_dropped(&self) -> Option<f64> {
+        self.tx_dropped
+    }
+
+    pub fn reset(&self) -> bool {
+        self.reset
+    }
+}
+
+/// Rates of the interfaces present in two snapshots of [Network].
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct NetworkRates {
+    /// Time between the snapshots in milliseconds.
+    elapsed: u128,
+    interfaces: HashMap<String, InterfaceRates>,
+}
+
+impl NetworkRates {
+    /// Compute the rates from `before` to `after`.
+    ///
+    /// Returns `None` if `after` was not taken after `before`.
+    pub fn new(before: &Network, after: &Network) -> Option<NetworkRates> {
+        let elapsed = after.timestamp.checked_sub(before.timestamp)?;
+        if elapsed == 0 {
+            return None;
+        }
+        let seconds = elapsed as f64 / 1000.;
+
+        let interfaces = after
+            .devices
+            .iter()
+            .filter_map(|(name, after)| {
+                let before = before.devices.get(name)?;
+                Some((name.clone(), InterfaceRates::new(before, after, seconds)))
+            })
+            .collect();
+
+        Some(NetworkRates {
+            elapsed,
+            interfaces,
+        })
+    }
+
+    pub fn elapsed(&self) -> u128 {
+        self.elapsed
+    }
+
+    pub fn interfaces(&self) -> &HashMap<String, InterfaceRates> {
+        &self.interfaces
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::parser::Parser;
+
+    #[test]
+    fn test_delta() {
+        let max = 250_000_000;
+
+        assert_eq!(delta(10, 25, max), Some(15));
+        assert_eq!(delta(u32::MAX as u64 - 5, 10, max), Some(16));
+        // reset to a value close to the previous one
+        assert_eq!(delta(1_000_000, 500, max), None);
+        // reset from the upper half of the range, a wrap would exceed the link speed
+        assert_eq!(delta(3_000_000_000, 100, max), None);
+        // 64-bit counters do not wrap in practice
+        assert_eq!(delta(1 << 40, 10, max), None);
+    }
+
+    #[test]
+    fn test_rates() {
+        let before = Network::parse().unwrap();
+        let mut after = before.clone();
+        after.timestamp += 2000;
+
+        let wlan = after.devices.get_mut("wlan0").unwrap();
+        wlan.statistics.rx_bytes += 4000;
+        wlan.statistics.tx_packets = 0;
+
+        let rates = NetworkRates::new(&before, &after).unwrap();
+        let wlan = &rates.interfaces["wlan0"];
+
+        assert_eq!(wlan.rx_bytes, Some(2000.));
+        assert_eq!(wlan.rx_packets, Some(0.));
+        assert_eq!(wlan.tx_packets, None);
+        assert!(wlan.reset);
+
+        after.devices.get_mut("wlan0").unwrap().ifindex = Some(12);
+        let rates = NetworkRates::new(&before, &after).unwrap();
+        assert_eq!(rates.interfaces["wlan0"].rx_bytes, None);
+
+        assert!(NetworkRates::new(&after, &before).is_none());
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    modules::process::PROC,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+/// The route is usable.
+const RTF_UP: u32 = 0x0001;
+/// The destination is reached through a gateway.
+const RTF_GATEWAY: u32 = 0x0002;
+
+/// An entry of the main routing table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Route {
+    interface: String,
+    destination: IpAddr,
+    prefix_len: u8,
+    gateway: Option<IpAddr>,
+    metric: u32,
+    /// `RTF_*` flags of `include/uapi/linux/route.h`.
+    flags: u32,
+}
+
+impl Route {
+    /// Parse a line of `/proc/net/route`, where addresses are in host byte order.
+    fn parse_ipv4(line: &str) -> Option<Route> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            return None;
+        }
+
+        let ipv4 = |field: &str| {
+            u32::from_str_radix(field, 16)
+                .ok()
+                .map(|ip| Ipv4Addr::from(ip.to_ne_bytes()))
+        };
+        let flags = u32::from_str_radix(fields[3], 16).ok()?;
+        let gateway = ipv4(fields[2])?;
+
+        Some(Route {
+            interface: fields[0].to_string(),
+            destination: IpAddr::V4(ipv4(fields[1])?),
+            prefix_len: ipv4(fields[7])?.to_bits().count_ones() as u8,
+            gateway: (flags & RTF_GATEWAY != 0).then_some(IpAddr::V4(gateway)),
+            metric: fields[6].parse().ok()?,
+            flags,
+        })
+    }
+
+    /// Parse a line of `/proc/net/ipv6_route`.
+    fn parse_ipv6(line: &str) -> Option<Route> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+
+        let ipv6 = |field: &str| u128::from_str_radix(field, 16).ok().map(Ipv6Addr::from);
+        let gateway = ipv6(fields[4])?;
+
+        Some(Route {
+            interface: fields[9].to_string(),
+            destination: IpAddr::V6(ipv6(fields[0])?),
+            prefix_len: u8::from_str_radix(fields[1], 16).ok()?,
+            gateway: (!gateway.is_unspecified()).then_some(IpAddr::V6(gateway)),
+            metric: u32::from_str_radix(fields[5], 16).ok()?,
+            flags: u32::from_str_radix(fields[8], 16).ok()?,
+        })
+    }
+
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    pub fn destination(&self) -> IpAddr {
+        self.destination
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    pub fn gateway(&self) -> Option<IpAddr> {
+        self.gateway
+    }
+
+    pub fn metric(&self) -> u32 {
+        self.metric
+    }
+
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    /// Whether this is a usable route to any destination.
+    pub fn is_default(&self) -> bool {
+        self.prefix_len == 0 && self.flags & RTF_UP != 0
+    }
+
+    /// Whether `ip` is in the destination network of this route.
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.destination, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX
+                    .checked_shl(32 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                network.to_bits() & mask == ip.to_bits() & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX
+                    .checked_shl(128 - self.prefix_len as u32)
+                    .unwrap_or(0);
+                network.to_bits() & mask == ip.to_bits() & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Routing tables of a network namespace, from `/proc/net/route` and
+/// `/proc/net/ipv6_route`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Routes {
+    routes: Vec<Route>,
+}
+
+impl Parser for Routes {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        Routes::read(&format!("{}net/", PROC))
+    }
+}
+
+impl Routes {
+    /// Read the routes of the network namespace of a process.
+    pub fn from_pid(pid: u32) -> Result<Routes, DataError> {
+        Routes::read(&format!("{}{}/net/", PROC, pid))
+    }
+
+    fn read(dir: &str) -> Result<Routes, DataError> {
+        let ipv4 =
+            fs::read_to_string(format!("{}route", dir)).map_err(|_| DataError::FileNotFound)?;
+
+        let mut routes = ipv4
+            .lines()
+            // Header
+            .skip(1)
+            .map(|line| Route::parse_ipv4(line).ok_or(DataError::Parsing))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // IPv6 may be disabled
+        if let Ok(ipv6) = fs::read_to_string(format!("{}ipv6_route", dir)) {
+            for line in ipv6.lines() {
+                routes.push(Route::parse_ipv6(line).ok_or(DataError::Parsing)?);
+            }
+        }
+
+        Ok(Routes { routes })
+    }
+
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /// The IPv4 default route with the lowest metric.
+    pub fn default_gateway(&self) -> Option<&Route> {
+        self.default_route(|ip| ip.is_ipv4())
+    }
+
+    /// The IPv6 default route with the lowest metric.
+    pub fn default_gateway6(&self) -> Option<&Route> {
+        self.default_route(|ip| ip.is_ipv6())
+    }
+
+    fn default_route(&self, family: fn(&IpAddr) -> bool) -> Option<&Route> {
+        self.routes
+            .iter()
+            .filter(|route| route.is_default() && family(&route.destination))
+            .filter(|route| route.gateway.is_some())
+            .min_by_key(|route| route.metric)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let routes = Routes::parse().unwrap();
+
+        assert_eq!(routes.routes.len(), 6);
+
+        let link = &routes.routes[1];
+        assert_eq!(link.destination, "192.168.1.0".parse::<IpAddr>().unwrap());
+        assert_eq!(link.prefix_len, 24);
+        assert_eq!(link.gateway, None);
+        assert!(link.contains(&"192.168.1.14".parse().unwrap()));
+        assert!(!link.contains(&"192.168.2.14".parse().unwrap()));
+
+        let gateway = routes.default_gateway().unwrap();
+        assert_eq!(gateway.interface(), "wlan0");
+        assert_eq!(gateway.gateway, Some("192.168.1.1".parse().unwrap()));
+        assert_eq!(gateway.metric, 600);
+
+        let gateway6 = routes.default_gateway6().unwrap();
+        assert_eq!(gateway6.gateway, Some("fe80::1".parse().unwrap()));
+        assert_eq!(routes.routes[2].prefix_len, 64);
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    modules::process::PROC,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs};
+
+/// Prefixes of the counters of `/proc/net/snmp6`, longest first.
+const SNMP6_PROTOCOLS: [&str; 4] = ["UdpLite6", "Icmp6", "Udp6", "Ip6"];
+
+/// Counters grouped by protocol, e.g. `Tcp` → `RetransSegs`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtocolCounters {
+    protocols: HashMap<String, HashMap<String, i64>>,
+}
+
+impl ProtocolCounters {
+    pub fn get(&self, protocol: &str, counter: &str) -> Option<i64> {
+        self.protocols.get(protocol)?.get(counter).copied()
+    }
+
+    pub fn protocols(&self) -> &HashMap<String, HashMap<String, i64>> {
+        &self.protocols
+    }
+
+    /// Parse the format of `/proc/net/snmp` and `/proc/net/netstat`, where a
+    /// line of names is followed by a line of values for every protocol:
+    ///
+    /// ```text
+    /// Udp: InDatagrams NoPorts InErrors
+    /// Udp: 401128 2231 187
+    /// ```
+    fn from_tables(content: &str) -> Option<ProtocolCounters> {
+        let mut protocols = HashMap::new();
+        let mut lines = content.lines();
+
+        while let Some(names) = lines.next() {
+            let (protocol, names) = names.split_once(':')?;
+            let (_, values) = lines.next()?.split_once(':')?;
+
+            let counters = names
+                .split_whitespace()
+                .zip(values.split_whitespace())
+                .map(|(name, value)| Some((name.to_string(), value.parse().ok()?)))
+                .collect::<Option<_>>()?;
+
+            protocols.insert(protocol.to_string(), counters);
+        }
+
+        Some(ProtocolCounters { protocols })
+    }
+
+    /// Parse the format of `/proc/net/snmp6`, where every line holds a name
+    /// prefixed by the protocol and a value, e.g. `Udp6InErrors 5`.
+    fn from_snmp6(content: &str) -> Option<ProtocolCounters> {
+        let mut protocols: HashMap<String, HashMap<String, i64>> = HashMap::new();
+
+        for line in content.lines() {
+            let (name, value) = line.split_once(char::is_whitespace)?;
+            let (protocol, name) = SNMP6_PROTOCOLS
+                .iter()
+                .find_map(|protocol| Some((*protocol, name.strip_prefix(protocol)?)))
+                .unwrap_or(("", name));
+
+            protocols
+                .entry(protocol.to_string())
+                .or_default()
+                .insert(name.to_string(), value.trim().parse().ok()?);
+        }
+
+        Some(ProtocolCounters { protocols })
+    }
+
+    /// Parse the format of `/proc/net/sockstat`, where every line holds
+    /// pairs of names and values, e.g. `UDP: inuse 4 mem 2`.
+    fn from_pairs(content: &str) -> Option<ProtocolCounters> {
+        let mut protocols = HashMap::new();
+
+        for line in content.lines() {
+            let (protocol, pairs) = line.split_once(':')?;
+            let pairs: Vec<&str> = pairs.split_whitespace().collect();
+
+            let counters = pairs
+                .chunks(2)
+                .map(|pair| match pair {
+                    [name, value] => Some((name.to_string(), value.parse().ok()?)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?;
+
+            protocols.insert(protocol.to_string(), counters);
+        }
+
+        Some(ProtocolCounters { protocols })
+    }
+}
+
+/// Protocol counters of a network namespace, from `/proc/net/snmp`,
+/// `/proc/net/netstat`, `/proc/net/snmp6` and `/proc/net/sockstat`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolStatistics {
+    /// Counters of RFC 1213 (`Ip`, `Icmp`, `Tcp`, `Udp`, ...).
+    snmp: ProtocolCounters,
+    /// Linux specific counters (`TcpExt`, `IpExt`, ...).
+    netstat: ProtocolCounters,
+    /// IPv6 counters, `None` if IPv6 is disabled.
+    snmp6: Option<ProtocolCounters>,
+    /// Sockets in use and their memory, in pages.
+    sockstat: ProtocolCounters,
+}
+
+impl Parser for ProtocolStatistics {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        ProtocolStatistics::read(&format!("{}net/", PROC))
+    }
+}
+
+impl ProtocolStatistics {
+    /// Read the counters of the network namespace of a process.
+    pub fn from_pid(pid: u32) -> Result<ProtocolStatistics, DataError> {
+        ProtocolStatistics::read(&format!("{}{}/net/", PROC, pid))
+    }
+
+    fn read(dir: &str) -> Result<ProtocolStatistics, DataError> {
+        let read = |name: &str| {
+            fs::read_to_string(format!("{}{}", dir, name)).map_err(|_| DataError::FileNotFound)
+        };
+
+        Ok(ProtocolStatistics {
+            snmp: ProtocolCounters::from_tables(&read("snmp")?).ok_or(DataError::Parsing)?,
+            netstat: ProtocolCounters::from_tables(&read("netstat")?).ok_or(DataError::Parsing)?,
+            snmp6: match read("snmp6") {
+                Ok(content) => {
+                    Some(ProtocolCounters::from_snmp6(&content).ok_or(DataError::Parsing)?)
+                }
+                Err(_) => None,
+            },
+            sockstat: ProtocolCounters::from_pairs(&read("sockstat")?).ok_or(DataError::Parsing)?,
+        })
+    }
+
+    pub fn snmp(&self) -> &ProtocolCounters {
+        &self.snmp
+    }
+
+    pub fn netstat(&self) -> &ProtocolCounters {
+        &self.netstat
+    }
+
+    pub fn snmp6(&self) -> Option<&ProtocolCounters> {
+        self.snmp6.as_ref()
+    }
+
+    pub fn sockstat(&self) -> &ProtocolCounters {
+        &self.sockstat
+    }
+
+    /// TCP segments retransmitted.
+    pub fn tcp_retransmits(&self) -> Option<i64> {
+        self.snmp.get("Tcp", "RetransSegs")
+    }
+
+    /// TCP segments sent with the RST flag.
+    pub fn tcp_resets(&self) -> Option<i64> {
+        self.snmp.get("Tcp", "OutRsts")
+    }
+
+    /// Connections dropped because the accept queue of a listening socket was full.
+    pub fn listen_overflows(&self) -> Option<i64> {
+        self.netstat.get("TcpExt", "ListenOverflows")
+    }
+
+    /// UDP datagrams that could not be delivered, e.g. because the receive
+    /// buffer was full.
+    pub fn udp_receive_errors(&self) -> Option<i64> {
+        self.snmp.get("Udp", "InErrors")
+    }
+
+    /// Memory used by TCP sockets, in pages.
+    pub fn tcp_memory(&self) -> Option<i64> {
+        self.sockstat.get("TCP", "mem")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let statistics = ProtocolStatistics::parse().unwrap();
+
+        assert_eq!(statistics.tcp_retransmits(), Some(18244));
+        assert_eq!(statistics.snmp.get("Tcp", "MaxConn"), Some(-1));
+        assert_eq!(statistics.listen_overflows(), Some(312));
+        assert_eq!(statistics.udp_receive_errors(), Some(187));
+        assert_eq!(
+            statistics.netstat.get("IpExt", "InOctets"),
+            Some(12204411092)
+        );
+
+        let snmp6 = statistics.snmp6().unwrap();
+        assert_eq!(snmp6.get("Udp6", "InErrors"), Some(5));
+        assert_eq!(snmp6.get("UdpLite6", "InDatagrams"), Some(0));
+        assert_eq!(snmp6.get("Ip6", "OutRequests"), Some(98711));
+
+        assert_eq!(statistics.sockstat.get("sockets", "used"), Some(291));
+        assert_eq!(statistics.tcp_memory(), Some(3));
+    }
+
+    #[test]
+    fn test_tables_mismatch() {
+        assert!(ProtocolCounters::from_tables("Tcp: RtoMin\n").is_none());
+        assert!(ProtocolCounters::from_pairs("TCP: inuse\n").is_none());
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{
+    modules::process::{Process, PROC},
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+/// Table of `/proc/net` a socket was read from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Raw,
+    Raw6,
+}
+
+impl Protocol {
+    const ALL: [Protocol; 6] = [
+        Protocol::Tcp,
+        Protocol::Tcp6,
+        Protocol::Udp,
+        Protocol::Udp6,
+        Protocol::Raw,
+        Protocol::Raw6,
+    ];
+
+    /// Name of the file in `/proc/net`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Protocol::Tcp => "tcp",
+            Protocol::Tcp6 => "tcp6",
+            Protocol::Udp => "udp",
+            Protocol::Udp6 => "udp6",
+            Protocol::Raw => "raw",
+            Protocol::Raw6 => "raw6",
+        }
+    }
+}
+
+/// State of a socket, defined in `include/net/tcp_states.h`.
+///
+/// UDP and raw sockets use the same values, they are `Established` when
+/// connected and `Close` otherwise.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SocketState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    NewSynRecv,
+}
+
+impl SocketState {
+    fn from_code(code: u8) -> Option<SocketState> {
+        Some(match code {
+            0x01 => SocketState::Established,
+            0x02 => SocketState::SynSent,
+            0x03 => SocketState::SynRecv,
+            0x04 => SocketState::FinWait1,
+            0x05 => SocketState::FinWait2,
+            0x06 => SocketState::TimeWait,
+            0x07 => SocketState::Close,
+            0x08 => SocketState::CloseWait,
+            0x09 => SocketState::LastAck,
+            0x0A => SocketState::Listen,
+            0x0B => SocketState::Closing,
+            0x0C => SocketState::NewSynRecv,
+            _ => return None,
+        })
+    }
+}
+
+/// A line of `/proc/net/{tcp,tcp6,udp,udp6,raw,raw6}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InetSocket {
+    protocol: Protocol,
+    /// For raw sockets, the port is the IP protocol number.
+    local: SocketAddr,
+    remote: SocketAddr,
+    state: SocketState,
+    /// Bytes in the send queue.
+    tx_queue: u32,
+    /// Bytes in the receive queue.
+    rx_queue: u32,
+    uid: u32,
+    /// Inode of the socket, 0 for sockets in `TIME_WAIT`.
+    inode: u64,
+}
+
+impl InetSocket {
+    fn parse(protocol: Protocol, line: &str) -> Option<InetSocket> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+
+        let (tx_queue, rx_queue) = fields[4].split_once(':')?;
+
+        Some(InetSocket {
+            protocol,
+            local: parse_address(fields[1])?,
+            remote: parse_address(fields[2])?,
+            state: SocketState::from_code(u8::from_str_radix(fields[3], 16).ok()?)?,
+            tx_queue: u32::from_str_radix(tx_queue, 16).ok()?,
+            rx_queue: u32::from_str_radix(rx_queue, 16).ok()?,
+            uid: fields[7].parse().ok()?,
+            inode: fields[9].parse().ok()?,
+        })
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn local(&self) -> SocketAddr {
+        self.local
+    }
+
+    pub fn remote(&self) -> SocketAddr {
+        self.remote
+    }
+
+    pub fn state(&self) -> SocketState {
+        self.state
+    }
+
+    pub fn tx_queue(&self) -> u32 {
+        self.tx_queue
+    }
+
+    pub fn rx_queue(&self) -> u32 {
+        self.rx_queue
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    pub fn inode(&self) -> u64 {
+        self.inode
+    }
+}
+
+/// Decode an address such as `0100007F:0277`.
+///
+/// The kernel prints the address as 32-bit words in host byte order, and the
+/// port in hexadecimal.
+fn parse_address(address: &str) -> Option<SocketAddr> {
+    let (ip, port) = address.split_once(':')?;
+    let port = u16::from_str_radix(port, 16).ok()?;
+
+    let mut bytes = Vec::with_capacity(16);
+    for i in (0..ip.len()).step_by(8) {
+        let word = u32::from_str_radix(ip.get(i..i + 8)?, 16).ok()?;
+        bytes.extend(word.to_ne_bytes());
+    }
+
+    let ip = match bytes.len() {
+        4 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?)),
+        16 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?)),
+        _ => return None,
+    };
+
+    Some(SocketAddr::new(ip, port))
+}
+
+/// Type of a UNIX socket.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnixType {
+    Stream,
+    Dgram,
+    SeqPacket,
+}
+
+/// A line of `/proc/net/unix`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UnixSocket {
+    kind: Option<UnixType>,
+    /// Whether the socket is accepting connections.
+    listening: bool,
+    /// Whether the socket is connected to a peer.
+    connected: bool,
+    inode: u64,
+    /// Path the socket is bound to, starting with `@` for abstract sockets.
+    path: Option<String>,
+}
+
+impl UnixSocket {
+    fn parse(line: &str) -> Option<UnixSocket> {
+        let mut fields = line.split_whitespace();
+        let mut field = || fields.next();
+
+        let _num = field()?;
+        let _ref_count = field()?;
+        let _protocol = field()?;
+        let flags = u32::from_str_radix(field()?, 16).ok()?;
+        let kind = u16::from_str_radix(field()?, 16).ok()?;
+        let state = u8::from_str_radix(field()?, 16).ok()?;
+        let inode = field()?.parse().ok()?;
+        // Paths may contain spaces
+        let path = line
+            .split_whitespace()
+            .skip(7)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Some(UnixSocket {
+            kind: match kind {
+                1 => Some(UnixType::Stream),
+                2 => Some(UnixType::Dgram),
+                5 => Some(UnixType::SeqPacket),
+                _ => None,
+            },
+            // __SO_ACCEPTCON
+            listening: flags & 0x0001_0000 != 0,
+            // SS_CONNECTED
+            connected: state == 3,
+            inode,
+            path: (!path.is_empty()).then_some(path),
+        })
+    }
+
+    pub fn kind(&self) -> Option<UnixType> {
+        self.kind
+    }
+
+    pub fn listening(&self) -> bool {
+        self.listening
+    }
+
+    pub fn connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn inode(&self) -> u64 {
+        self.inode
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+}
+
+/// Sockets of a network namespace.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Sockets {
+    inet: Vec<InetSocket>,
+    unix: Vec<UnixSocket>,
+}
+
+impl Parser for Sockets {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        Sockets::read(&format!("{}net/", PROC))
+    }
+}
+
+impl Sockets {
+    /// Read the sockets of the network namespace of a process.
+    pub fn from_pid(pid: u32) -> Result<Sockets, DataError> {
+        Sockets::read(&format!("{}{}/net/", PROC, pid))
+    }
+
+    fn read(dir: &str) -> Result<Sockets, DataError> {
+        let mut inet = vec![];
+
+        for protocol in Protocol::ALL {
+            // IPv6 or raw sockets may be disabled
+            let Ok(content) = fs::read_to_string(format!("{}{}", dir, protocol.name())) else {
+                continue;
+            };
+
+            for line in content.lines().skip(1) {
+                inet.push(InetSocket::parse(protocol, line).ok_or(DataError::Parsing)?);
+            }
+        }
+
+        let unix = fs::read_to_string(format!("{}unix", dir))
+            .map_err(|_| DataError::FileNotFound)?
+            .lines()
+            .skip(1)
+            .map(|line| UnixSocket::parse(line).ok_or(DataError::Parsing))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Sockets { inet, unix })
+    }
+
+    pub fn inet(&self) -> &[InetSocket] {
+        &self.inet
+    }
+
+    pub fn unix(&self) -> &[UnixSocket] {
+        &self.unix
+    }
+
+    /// TCP sockets listening on `port`, on any address.
+    pub fn listening_on(&self, port: u16) -> impl Iterator<Item = &InetSocket> {
+        self.inet.iter().filter(move |socket| {
+            matches!(socket.protocol, Protocol::Tcp | Protocol::Tcp6)
+                && socket.state == SocketState::Listen
+                && socket.local.port() == port
+        })
+    }
+}
+
+/// Inodes of the sockets opened by a process, from the links of `/proc/[pid]/fd`.
+///
+/// Reading the links of another user's process requires privileges, the
+/// sockets of processes that cannot be read are left out.
+pub fn inodes_of(pid: u32) -> Vec<u64> {
+    let Ok(dir) = fs::read_dir(format!("{}{}/fd", PROC, pid)) else {
+        return vec![];
+    };
+
+    dir.filter_map(|entry| {
+        let link = fs::read_link(entry.ok()?.path()).ok()?;
+        link.to_str()?
+            .strip_prefix("socket:[")?
+            .strip_suffix(']')?
+            .parse()
+            .ok()
+    })
+    .collect()
+}
+
+/// Map the inode of every socket to the pids of the processes that have it open.
+///
+/// A socket may be shared by several processes, e.g. after a fork.
+pub fn owners(processes: &[Process]) -> HashMap<u64, Vec<u32>> {
+    let mut owners: HashMap<u64, Vec<u32>> = HashMap::new();
+
+    for process in processes {
+        for inode in inodes_of(process.pid()) {
+            owners.entry(inode).or_default().push(process.pid());
+        }
+    }
+
+    owners
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::modules::process::Processes;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            parse_address("0100007F:0277"),
+            Some("127.0.0.1:631".parse().unwrap())
+        );
+        assert_eq!(
+            parse_address("0000000000000000FFFF00000F02000A:0016"),
+            Some("[::ffff:10.0.2.15]:22".parse().unwrap())
+        );
+        assert_eq!(parse_address("0100007:0277"), None);
+    }
+
+    #[test]
+    fn test_parse() {
+        let sockets = Sockets::parse().unwrap();
+
+        assert_eq!(sockets.inet.len(), 7);
+        let established = &sockets.inet[2];
+        assert_eq!(established.state, SocketState::Established);
+        assert_eq!(established.remote, "10.0.2.10:54334".parse().unwrap());
+        assert_eq!(sockets.inet[4].tx_queue, 0x24);
+        assert_eq!(sockets.inet[5].protocol, Protocol::Udp);
+
+        assert_eq!(sockets.unix.len(), 4);
+        assert!(sockets.unix[0].listening);
+        assert_eq!(sockets.unix[1].kind, Some(UnixType::Dgram));
+        assert!(sockets.unix[2].connected && sockets.unix[2].path.is_none());
+        assert_eq!(sockets.unix[3].path(), Some("@/tmp/.X11-unix/X0"));
+    }
+
+    #[test]
+    fn test_owners() {
+        let sockets = Sockets::parse().unwrap();
+        let processes = Processes::parse().unwrap();
+        let owners = owners(&processes);
+
+        let listener: Vec<&InetSocket> = sockets.listening_on(8080).collect();
+        assert_eq!(listener.len(), 1);
+        assert_eq!(owners[&listener[0].inode], vec![1]);
+        assert_eq!(owners[&12001], vec![1]);
+        assert!(!owners.contains_key(&18210));
+
+        let mut inodes = inodes_of(1);
+        inodes.sort_unstable();
+        assert_eq!(inodes, vec![12001, 31337, 31338]);
+        assert!(inodes_of(4242).is_empty());
+    }
+}
@@ -0,0 +1,432 @@
+use super::DEVICE_DIR;
+use crate::{
+    helper::read_value,
+    modules::process::PROC,
+    parser::{DataError, Parser},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// An interface enslaved to a bond, with its state as seen by the bonding driver.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BondSlave {
+    name: String,
+    /// `up`, `down`, `going back` or `going down`.
+    mii_status: Option<String>,
+    link_failures: Option<u64>,
+}
+
+impl BondSlave {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mii_status(&self) -> Option<&str> {
+        self.mii_status.as_deref()
+    }
+
+    pub fn link_failures(&self) -> Option<u64> {
+        self.link_failures
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bond {
+    name: String,
+    /// Bonding mode, e.g. `active-backup` or `802.3ad`.
+    mode: Option<String>,
+    mii_status: Option<String>,
+    /// Slave carrying the traffic, for the modes with a single one.
+    active_slave: Option<String>,
+    slaves: Vec<BondSlave>,
+}
+
+impl Bond {
+    fn parse(name: &str, path: &Path, proc_net: &str) -> Bond {
+        let bonding = path.join("bonding");
+        let value = |file: &str| -> Option<String> {
+            read_value::<String>(&bonding.join(file)).filter(|value| !value.is_empty())
+        };
+
+        // The slave details are only in procfs
+        let details = fs::read_to_string(format!("{}bonding/{}", proc_net, name))
+            .map(|content| parse_slaves(&content))
+            .unwrap_or_default();
+
+        let slaves = value("slaves")
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(|slave| {
+                let (mii_status, link_failures) = details.get(slave).cloned().unwrap_or_default();
+
+                BondSlave {
+                    name: slave.to_string(),
+                    mii_status,
+                    link_failures,
+                }
+            })
+            .collect();
+
+        Bond {
+            name: name.to_string(),
+            // e.g. `active-backup 1`
+            mode: value("mode").and_then(|mode| mode.split_whitespace().next().map(String::from)),
+            mii_status: value("mii_status"),
+            active_slave: value("active_slave"),
+            slaves,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn mode(&self) -> Option<&str> {
+        self.mode.as_deref()
+    }
+
+    pub fn mii_status(&self) -> Option<&str> {
+        self.mii_status.as_deref()
+    }
+
+    pub fn active_slave(&self) -> Option<&str> {
+        self.active_slave.as_deref()
+    }
+
+    pub fn slaves(&self) -> &[BondSlave] {
+        &self.slaves
+    }
+}
+
+/// Read the MII status and link failure count of every slave in
+/// `/proc/net/bonding/<bond>`, where each slave is a block starting with
+/// `Slave Interface: <name>`.
+fn parse_slaves(content: &str) -> HashMap<String, (Option<String>, Option<u64>)> {
+    let mut slaves: HashMap<String, (Option<String>, Option<u64>)> = HashMap::new();
+    let mut current: Option<&str> = None;
+
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            current = None;
+            continue;
+        };
+        let value = value.trim();
+
+        match (key, current) {
+            ("Slave Interface", _) => {
+                current = Some(value);
+                slaves.insert(value.to_string(), (None, None));
+            }
+            ("MII Status", Some(slave)) => {
+                slaves.entry(slave.to_string()).or_default().0 = Some(value.to_string());
+            }
+            ("Link Failure Count", Some(slave)) => {
+                slaves.entry(slave.to_string()).or_default().1 = value.parse().ok();
+            }
+            _ => {}
+        }
+    }
+
+    slaves
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Bridge {
+    name: String,
+    /// Whether the spanning tree protocol is enabled.
+    stp: Option<bool>,
+    ports: Vec<String>,
+}
+
+impl Bridge {
+    fn parse(name: &str, path: &Path) -> Bridge {
+        let mut ports: Vec<String> = fs::read_dir(path.join("brif"))
+            .map(|dir| {
+                dir.filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        ports.sort();
+
+        Bridge {
+            name: name.to_string(),
+            stp: read_value::<u8>(&path.join("bridge/stp_state")).map(|state| state != 0),
+            ports,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stp(&self) -> Option<bool> {
+        self.stp
+    }
+
+    pub fn ports(&self) -> &[String] {
+        &self.ports
+    }
+}
+
+/// An 802.1Q interface on top of another one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Vlan {
+    name: String,
+    id: u16,
+    parent: String,
+}
+
+impl Vlan {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    pub fn parent(&self) -> &str {
+        &self.parent
+    }
+}
+
+/// Parse `/proc/net/vlan/config`, made of two header lines followed by lines
+/// such as `eth0.100 | 100 | eth0`.
+fn parse_vlans(content: &str) -> Option<Vec<Vlan>> {
+    content
+        .lines()
+        .skip(2)
+        .map(|line| {
+            let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+            if fields.len() != 3 {
+                return None;
+            }
+
+            Some(Vlan {
+                name: fields[0].to_string(),
+                id: fields[1].parse().ok()?,
+                parent: fields[2].to_string(),
+            })
+        })
+        .collect()
+}
+
+/// One end of a veth pair.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Veth {
+    name: String,
+    ifindex: u32,
+    peer_ifindex: u32,
+    /// Name of the other end, `None` if it is in another network namespace.
+    peer: Option<String>,
+}
+
+impl Veth {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn ifindex(&self) -> u32 {
+        self.ifindex
+    }
+
+    pub fn peer_ifindex(&self) -> u32 {
+        self.peer_ifindex
+    }
+
+    pub fn peer(&self) -> Option<&str> {
+        self.peer.as_deref()
+    }
+}
+
+/// How the interfaces of the network namespace relate to each other.
+///
+/// sysfs does not tell the kind of an interface, so veth ends are recognised
+/// as virtual interfaces linked to another one (`iflink`) which are neither
+/// vlans nor stacked on a lower device (`lower_*`). Tunnels bound to a device
+/// may show up as veths.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct Topology {
+    bonds: Vec<Bond>,
+    bridges: Vec<Bridge>,
+    /// Empty if the `8021q` module is not loaded.
+    vlans: Vec<Vlan>,
+    veths: Vec<Veth>,
+}
+
+impl Parser for Topology {
+    fn parse() -> Result<Self, DataError>
+    where
+        Self: Sized,
+    {
+        Topology::read(Path::new(DEVICE_DIR), &format!("{}net/", PROC))
+    }
+}
+
+impl Topology {
+    fn read(class_net: &Path, proc_net: &str) -> Result<Topology, DataError> {
+        let mut interfaces: Vec<(String, PathBuf)> = fs::read_dir(class_net)
+            .map_err(|_| DataError::FileNotFound)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                Some((entry.file_name().into_string().ok()?, entry.path()))
+            })
+            .collect();
+        interfaces.sort();
+
+        let vlans = match fs::read_to_string(format!("{}vlan/config", proc_net)) {
+            Ok(content) => parse_vlans(&content).ok_or(DataError::Parsing)?,
+            Err(_) => vec![],
+        };
+
+        let names: HashMap<u32, &str> = interfaces
+            .iter()
+            .filter_map(|(name, path)| Some((read_value(path.join("ifindex"))?, name.as_str())))
+            .collect();
+
+        let mut topology = Topology {
+            vlans,
+            ..Default::default()
+        };
+
+        for (name, path) in &interfaces {
+            if path.join("bonding").is_dir() {
+                topology.bonds.push(Bond::parse(name, path, proc_net));
+            }
+
+            if path.join("bridge").is_dir() {
+                topology.bridges.push(Bridge::parse(name, path));
+            }
+
+            if let Some(veth) = Topology::veth(name, path, &names) {
+                topology.veths.push(veth);
+            }
+        }
+
+        Ok(topology)
+    }
+
+    fn veth(name: &str, path: &Path, names: &HashMap<u32, &str>) -> Option<Veth> {
+        let ifindex: u32 = read_value(path.join("ifindex"))?;
+        let iflink: u32 = read_value(path.join("iflink"))?;
+
+        let is_virtual = fs::symlink_metadata(path.join("device")).is_err();
+        let has_lower = fs::read_dir(path).ok()?.any(|entry| {
+            entry
+                .ok()
+                .and_then(|entry| entry.file_name().into_string().ok())
+                .is_some_and(|entry| entry.starts_with("lower_"))
+        });
+
+        if !is_virtual || iflink == ifindex || iflink == 0 || has_lower || is_vlan(path) {
+            return None;
+        }
+
+        Some(Veth {
+            name: name.to_string(),
+            ifindex,
+            peer_ifindex: iflink,
+            // The index may belong to an unrelated interface of this namespace
+            peer: names
+                .get(&iflink)
+                .filter(|peer| {
+                    read_value::<u32>(&path.with_file_name(peer).join("iflink")) == Some(ifindex)
+                })
+                .map(|peer| peer.to_string()),
+        })
+    }
+
+    pub fn bonds(&self) -> &[Bond] {
+        &self.bonds
+    }
+
+    pub fn bridges(&self) -> &[Bridge] {
+        &self.bridges
+    }
+
+    pub fn vlans(&self) -> &[Vlan] {
+        &self.vlans
+    }
+
+    pub fn veths(&self) -> &[Veth] {
+        &self.veths
+    }
+
+    /// Name of the bond or bridge the interface belongs to.
+    pub fn master(&self, name: &str) -> Option<&str> {
+        let bond = self
+            .bonds
+            .iter()
+            .find(|bond| bond.slaves.iter().any(|slave| slave.name == name))
+            .map(|bond| bond.name.as_str());
+
+        bond.or_else(|| {
+            self.bridges
+                .iter()
+                .find(|bridge| bridge.ports.iter().any(|port| port == name))
+                .map(|bridge| bridge.name.as_str())
+        })
+    }
+
+    /// Vlans on top of the interface.
+    pub fn vlans_of<'a>(&'a self, parent: &'a str) -> impl Iterator<Item = &'a Vlan> + 'a {
+        self.vlans.iter().filter(move |vlan| vlan.parent == parent)
+    }
+}
+
+fn is_vlan(path: &Path) -> bool {
+    fs::read_to_string(path.join("uevent")).is_ok_and(|uevent| uevent.contains("DEVTYPE=vlan"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let topology = Topology::read(
+            Path::new("./mock/topology/sysclassnet/"),
+            "./mock/topology/proc/net/",
+        )
+        .unwrap();
+
+        let bond = &topology.bonds[0];
+        assert_eq!(bond.mode(), Some("active-backup"));
+        assert_eq!(bond.active_slave(), Some("eth0"));
+        assert_eq!(
+            bond.slaves[1],
+            BondSlave {
+                name: "eth1".to_string(),
+                mii_status: Some("down".to_string()),
+                link_failures: Some(3),
+            }
+        );
+
+        assert_eq!(topology.bridges[0].ports, vec!["bond0.100", "veth1a"]);
+        assert_eq!(topology.bridges[0].stp, Some(false));
+
+        assert_eq!(topology.vlans_of("bond0").next().map(Vlan::id), Some(100));
+
+        assert_eq!(topology.master("eth1"), Some("bond0"));
+        assert_eq!(topology.master("veth1a"), Some("br0"));
+        assert_eq!(topology.master("bond0"), None);
+
+        let veths: Vec<(&str, Option<&str>)> = topology
+            .veths
+            .iter()
+            .map(|veth| (veth.name(), veth.peer()))
+            .collect();
+        assert_eq!(
+            veths,
+            vec![
+                ("veth1a", None),
+                ("veth2a", Some("veth2b")),
+                ("veth2b", Some("veth2a"))
+            ]
+        );
+    }
+}
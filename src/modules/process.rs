@@ -1,16 +1,96 @@
-use crate::parser::{DataError, Parser as PParser};
+use crate::{
+    helper::iso8601,
+    modules::{
+        decode::{Flags, Policy, ProcessFlags, SignalSet, Tty},
+        handle::ProcessId,
+        maps::MemoryMaps,
+        misc::Misc,
+        namespace::{self, NamespaceKind, Namespaces},
+    },
+    parser::{DataError, Parser as PParser},
+};
 use pest::Parser;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Error, fs, os::unix::fs::MetadataExt, str::FromStr};
+use std::{
+    fmt::Error,
+    fs::{self, File, ReadDir},
+    io::{self, Read},
+    os::unix::fs::MetadataExt,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+#[cfg(test)]
+pub(crate) const PROC: &str = "./mock/proc/";
+#[cfg(not(test))]
+pub(crate) const PROC: &str = "/proc/";
 
 pub type Processes = Vec<Process>;
 
+/// Options to control which processes are collected, and which of their files are read.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Leave kernel threads out, refer to [Process::is_kernel_thread].
+    pub skip_kernel_threads: bool,
+    /// Look up the name of the owner of the process.
+    pub user_name: bool,
+    /// Read `/proc/[pid]/ns/*` and `/proc/[pid]/status`, refer to [Process::namespaces].
+    pub namespaces: bool,
+    /// Compute [Process::started_at].
+    pub start_time: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            skip_kernel_threads: false,
+            user_name: true,
+            namespaces: true,
+            start_time: true,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Only read `/proc/[pid]/stat`.
+    pub fn minimal() -> Self {
+        ParseOptions {
+            skip_kernel_threads: false,
+            user_name: false,
+            namespaces: false,
+            start_time: false,
+        }
+    }
+}
+
+/// Error while reading a single process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProcessError {
+    /// The process exited during the scan, this can usually be ignored.
+    Vanished(u32),
+    /// Not allowed to read the files of the process.
+    PermissionDenied(u32),
+    /// The files of the process could not be parsed.
+    Parsing(u32),
+}
+
+impl ProcessError {
+    pub fn pid(&self) -> u32 {
+        match self {
+            ProcessError::Vanished(pid)
+            | ProcessError::PermissionDenied(pid)
+            | ProcessError::Parsing(pid) => *pid,
+        }
+    }
+}
+
 #[derive(pest_derive::Parser)]
 #[grammar = "stat.pest"]
 struct StatParser;
 
-#[derive(Serialize, Deserialize, Debug)]
-enum State {
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
     /// Sleeping
     S = 1,
     /// Idle
@@ -44,7 +124,7 @@ impl FromStr for State {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Process {
     user_name: Option<String>,
     /// The process ID.
@@ -64,7 +144,7 @@ pub struct Process {
     /// The session ID of the process.
     session: u32,
     ///  The controlling terminal of the process. (The minor device number is contained in the combination of bits 31 to 20 and 7 to 0; the major device number is in bits 15 to 8.)
-    tty_nr: u32,
+    tty_nr: Tty,
     /// The ID of the foreground process group of the controlling terminal of the process.
     tpgid: i32,
     /// The kernel flags word of the process.  For bit
@@ -73,7 +153,7 @@ pub struct Process {
     /// on the kernel version.
     ///
     /// The format for this field was %lu before Linux 2.6.
-    flags: u32,
+    flags: Flags,
     /// The number of minor faults the process has made
     /// which have not required loading a memory page from
     /// disk.
@@ -99,17 +179,17 @@ pub struct Process {
     startstack: u64,
     kstkep: u64,
     kstkeip: u64,
-    signal: u64,
-    blocked: u64,
-    sigignore: u64,
-    sigcatch: u64,
+    signal: SignalSet,
+    blocked: SignalSet,
+    sigignore: SignalSet,
+    sigcatch: SignalSet,
     wchan: u64,
     nswap: u64,
     cnswap: u64,
     exit_signal: i32,
     processor: i32,
     rt_priotiy: u32,
-    policy: u32,
+    policy: Policy,
     delayacct_blkio_ticks: u64,
     guest_time: u64,
     cguest_time: u64,
@@ -121,10 +201,35 @@ pub struct Process {
     env_start: u64,
     env_end: u64,
     exit: i32,
+    /// Namespaces the process belongs to.
+    namespaces: Namespaces,
+    /// Pid of the process in each nested pid namespace, starting from the one of `/proc`.
+    nspid: Vec<u32>,
+    /// Thread group id of the process in each nested pid namespace.
+    nstgid: Vec<u32>,
+    /// Start time of the process in ISO 8601 format.
+    started_at: Option<String>,
+    /// Buffer reused by [Process::refresh] to read the stat file.
+    #[serde(skip)]
+    buffer: String,
+}
+
+/// Outcome of [Process::refresh].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefreshStatus {
+    /// The process is still running and was updated.
+    Updated,
+    /// The process has exited, it was left untouched.
+    Exited,
+    /// The pid now belongs to another process, it was left untouched.
+    Replaced,
 }
 
 impl Process {
-    fn new(stat: &str, name: Option<String>) -> Result<Process, Box<dyn std::error::Error>> {
+    pub(crate) fn new(
+        stat: &str,
+        name: Option<String>,
+    ) -> Result<Process, Box<dyn std::error::Error>> {
         log::debug!("{}", stat);
 
         let stats: Vec<_> = StatParser::parse(Rule::line, stat)?.collect();
@@ -141,9 +246,9 @@ impl Process {
             ppid: stats[3].as_str().parse()?,
             pgrp: stats[4].as_str().parse()?,
             session: stats[5].as_str().parse()?,
-            tty_nr: stats[6].as_str().parse()?,
+            tty_nr: Tty::from(stats[6].as_str().parse::<u32>()?),
             tpgid: stats[7].as_str().parse()?,
-            flags: stats[8].as_str().parse()?,
+            flags: Flags::from(stats[8].as_str().parse::<u32>()?),
             minflit: stats[9].as_str().parse()?,
             cminflit: stats[10].as_str().parse()?,
             majflt: stats[11].as_str().parse()?,
@@ -165,17 +270,17 @@ impl Process {
             startstack: stats[27].as_str().parse()?,
             kstkep: stats[28].as_str().parse()?,
             kstkeip: stats[29].as_str().parse()?,
-            signal: stats[30].as_str().parse()?,
-            blocked: stats[31].as_str().parse()?,
-            sigignore: stats[32].as_str().parse()?,
-            sigcatch: stats[33].as_str().parse()?,
+            signal: SignalSet::from(stats[30].as_str().parse::<u64>()?),
+            blocked: SignalSet::from(stats[31].as_str().parse::<u64>()?),
+            sigignore: SignalSet::from(stats[32].as_str().parse::<u64>()?),
+            sigcatch: SignalSet::from(stats[33].as_str().parse::<u64>()?),
             wchan: stats[34].as_str().parse()?,
             nswap: stats[35].as_str().parse()?,
             cnswap: stats[36].as_str().parse()?,
             exit_signal: stats[37].as_str().parse()?,
             processor: stats[38].as_str().parse()?,
             rt_priotiy: stats[39].as_str().parse()?,
-            policy: stats[40].as_str().parse()?,
+            policy: Policy::from(stats[40].as_str().parse::<u32>()?),
             delayacct_blkio_ticks: stats[41].as_str().parse()?,
             guest_time: stats[42].as_str().parse()?,
             cguest_time: stats[43].as_str().parse()?,
@@ -187,47 +292,472 @@ impl Process {
             env_start: stats[49].as_str().parse()?,
             env_end: stats[50].as_str().parse()?,
             exit: stats[51].as_str().parse()?,
+            namespaces: Namespaces::default(),
+            nspid: vec![],
+            nstgid: vec![],
+            started_at: None,
+            buffer: String::new(),
         })
     }
+
+    /// Read a single process, without walking all of `/proc`.
+    ///
+    /// This reads [Misc] on every call, use [Process::from_pid_with] to look
+    /// up processes repeatedly.
+    pub fn from_pid(pid: u32) -> Result<Process, DataError> {
+        Process::from_pid_with(pid, &ParseOptions::default(), Misc::parse().ok().as_ref())
+    }
+
+    /// Read a single process with the given options, `misc` is needed for
+    /// [ParseOptions::start_time].
+    pub fn from_pid_with(
+        pid: u32,
+        options: &ParseOptions,
+        misc: Option<&Misc>,
+    ) -> Result<Process, DataError> {
+        let mut process = Process::read_stat(pid)?;
+        if options.user_name {
+            process.user_name = user_name(Path::new(&format!("{}{}", PROC, pid)));
+        }
+        process.read_extras(options, misc);
+
+        Ok(process)
+    }
+
+    /// Read the process running this code.
+    pub fn current() -> Result<Process, DataError> {
+        Process::from_pid(std::process::id())
+    }
+
+    /// Update the statistics of the process by reading `/proc/[pid]/stat` again.
+    ///
+    /// If the process has exited or its pid was reused, it is left untouched.
+    pub fn refresh(&mut self) -> Result<RefreshStatus, DataError> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.clear();
+
+        let read = File::open(format!("{}{}/stat", PROC, self.pid))
+            .and_then(|mut file| file.read_to_string(&mut buffer));
+
+        let status = match read {
+            Err(_) => Ok(RefreshStatus::Exited),
+            Ok(_) => self.update(&buffer).map_err(|_| DataError::Parsing),
+        };
+
+        self.buffer = buffer;
+
+        status
+    }
+
+    /// Update the process from the contents of its `/proc/[pid]/stat`, in
+    /// place, so that polling does not allocate a new [Process].
+    ///
+    /// The process is left untouched if it was replaced.
+    fn update(&mut self, stat: &str) -> Result<RefreshStatus, Box<dyn std::error::Error>> {
+        // The command may contain spaces, it ends at the last parenthesis
+        let (head, rest) = stat.trim_end().rsplit_once(") ").ok_or(Error)?;
+        let (pid, command) = head.split_once(" (").ok_or(Error)?;
+
+        // Fields after the command start at `state`, the third one
+        if rest.split_whitespace().count() != 50 {
+            return Err(Box::new(Error));
+        }
+        let starttime: u64 = rest.split_whitespace().nth(19).ok_or(Error)?.parse()?;
+        if pid.parse::<u32>()? != self.pid || starttime != self.starttime {
+            return Ok(RefreshStatus::Replaced);
+        }
+
+        if self.command != command {
+            self.command.clear();
+            self.command.push_str(command);
+        }
+
+        let mut fields = rest.split_whitespace();
+        let mut next = || fields.next().ok_or(Error);
+
+        self.state = State::from_str(next()?)?;
+        self.ppid = next()?.parse()?;
+        self.pgrp = next()?.parse()?;
+        self.session = next()?.parse()?;
+        self.tty_nr = Tty::from(next()?.parse::<u32>()?);
+        self.tpgid = next()?.parse()?;
+        self.flags = Flags::from(next()?.parse::<u32>()?);
+        self.minflit = next()?.parse()?;
+        self.cminflit = next()?.parse()?;
+        self.majflt = next()?.parse()?;
+        self.cmajflt = next()?.parse()?;
+        self.utime = next()?.parse()?;
+        self.stime = next()?.parse()?;
+        self.cutime = next()?.parse()?;
+        self.cstime = next()?.parse()?;
+        self.priority = next()?.parse()?;
+        self.nice = next()?.parse()?;
+        self.num_threads = next()?.parse()?;
+        self.itrealvalue = next()?.parse()?;
+        self.starttime = next()?.parse()?;
+        self.vsize = next()?.parse()?;
+        self.rss = next()?.parse()?;
+        self.rsslim = next()?.parse()?;
+        self.startcode = next()?.parse()?;
+        self.encode = next()?.parse()?;
+        self.startstack = next()?.parse()?;
+        self.kstkep = next()?.parse()?;
+        self.kstkeip = next()?.parse()?;
+        self.signal = SignalSet::from(next()?.parse::<u64>()?);
+        self.blocked = SignalSet::from(next()?.parse::<u64>()?);
+        self.sigignore = SignalSet::from(next()?.parse::<u64>()?);
+        self.sigcatch = SignalSet::from(next()?.parse::<u64>()?);
+        self.wchan = next()?.parse()?;
+        self.nswap = next()?.parse()?;
+        self.cnswap = next()?.parse()?;
+        self.exit_signal = next()?.parse()?;
+        self.processor = next()?.parse()?;
+        self.rt_priotiy = next()?.parse()?;
+        self.policy = Policy::from(next()?.parse::<u32>()?);
+        self.delayacct_blkio_ticks = next()?.parse()?;
+        self.guest_time = next()?.parse()?;
+        self.cguest_time = next()?.parse()?;
+        self.start_data = next()?.parse()?;
+        self.end_data = next()?.parse()?;
+        self.start_brk = next()?.parse()?;
+        self.arg_start = next()?.parse()?;
+        self.arg_end = next()?.parse()?;
+        self.env_start = next()?.parse()?;
+        self.env_end = next()?.parse()?;
+        self.exit = next()?.parse()?;
+
+        Ok(RefreshStatus::Updated)
+    }
+
+    /// Read the process with the given pid from `/proc/[pid]/stat`.
+    pub(crate) fn read_stat(pid: u32) -> Result<Process, DataError> {
+        let stat = fs::read_to_string(format!("{}{}/stat", PROC, pid))
+            .map_err(|_| DataError::ProcessExited)?;
+
+        Process::new(&stat, None).map_err(|_| DataError::Parsing)
+    }
+
+    /// Numeric statistics of the process that may change over its lifetime.
+    pub(crate) fn counters(&self) -> [(&'static str, i128); 23] {
+        [
+            ("minflit", self.minflit.into()),
+            ("cminflit", self.cminflit.into()),
+            ("majflt", self.majflt.into()),
+            ("cmajflt", self.cmajflt.into()),
+            ("utime", self.utime.into()),
+            ("stime", self.stime.into()),
+            ("cutime", self.cutime.into()),
+            ("cstime", self.cstime.into()),
+            ("priority", self.priority.into()),
+            ("nice", self.nice.into()),
+            ("num_threads", self.num_threads.into()),
+            ("vsize", self.vsize.into()),
+            ("rss", self.rss.into()),
+            ("rsslim", self.rsslim.into()),
+            ("nswap", self.nswap.into()),
+            ("cnswap", self.cnswap.into()),
+            ("processor", self.processor.into()),
+            ("rt_priotiy", self.rt_priotiy.into()),
+            ("delayacct_blkio_ticks", self.delayacct_blkio_ticks.into()),
+            ("guest_time", self.guest_time.into()),
+            ("cguest_time", self.cguest_time.into()),
+            ("start_brk", self.start_brk.into()),
+            ("end_data", self.end_data.into()),
+        ]
+    }
+
+    /// Read the information of the process beyond `/proc/[pid]/stat`.
+    fn read_extras(&mut self, options: &ParseOptions, misc: Option<&Misc>) {
+        if options.namespaces {
+            self.namespaces = Namespaces::from_pid(self.pid);
+            (self.nspid, self.nstgid) = namespace::read_ns_ids(self.pid);
+        }
+        if options.start_time {
+            self.started_at = misc.and_then(|misc| self.start_time(misc)).map(iso8601);
+        }
+    }
+
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn user_name(&self) -> Option<&str> {
+        self.user_name.as_deref()
+    }
+
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn ppid(&self) -> u32 {
+        self.ppid
+    }
+
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    pub fn nice(&self) -> i32 {
+        self.nice
+    }
+
+    pub fn num_threads(&self) -> i32 {
+        self.num_threads
+    }
+
+    /// Virtual memory size in bytes.
+    pub fn vsize(&self) -> u64 {
+        self.vsize
+    }
+
+    /// Resident set size in pages.
+    pub fn rss(&self) -> i32 {
+        self.rss
+    }
+
+    /// Soft limit in bytes on the resident set size.
+    pub fn rsslim(&self) -> u64 {
+        self.rsslim
+    }
+
+    /// Read the memory mappings of the process from `/proc/[pid]/maps`.
+    pub fn maps(&self) -> Result<MemoryMaps, DataError> {
+        MemoryMaps::from_pid(self.pid)
+    }
+
+    pub fn tty(&self) -> &Tty {
+        &self.tty_nr
+    }
+
+    pub fn flags(&self) -> Flags {
+        self.flags
+    }
+
+    /// Pending signals.
+    pub fn signal(&self) -> &SignalSet {
+        &self.signal
+    }
+
+    /// Blocked signals.
+    pub fn blocked(&self) -> &SignalSet {
+        &self.blocked
+    }
+
+    /// Ignored signals.
+    pub fn sigignore(&self) -> &SignalSet {
+        &self.sigignore
+    }
+
+    /// Caught signals.
+    pub fn sigcatch(&self) -> &SignalSet {
+        &self.sigcatch
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy
+    }
+
+    /// Time the process started at.
+    pub fn start_time(&self, misc: &Misc) -> Option<SystemTime> {
+        let since_boot = self.ticks(self.starttime, misc)?;
+
+        let boot = match misc.boot_time() {
+            Some(btime) => SystemTime::UNIX_EPOCH + Duration::from_secs(btime),
+            None => SystemTime::now() - Duration::try_from_secs_f64(misc.uptime()?).ok()?,
+        };
+
+        Some(boot + since_boot)
+    }
+
+    /// Time elapsed since the process started.
+    pub fn run_time(&self, misc: &Misc) -> Option<Duration> {
+        let since_boot = self.ticks(self.starttime, misc)?;
+
+        match misc.uptime() {
+            Some(uptime) => Some(
+                Duration::try_from_secs_f64(uptime)
+                    .ok()?
+                    .saturating_sub(since_boot),
+            ),
+            None => SystemTime::now()
+                .duration_since(self.start_time(misc)?)
+                .ok(),
+        }
+    }
+
+    /// CPU time spent by the process in user and kernel mode.
+    pub fn cpu_time(&self, misc: &Misc) -> Option<Duration> {
+        self.ticks(self.utime as u64 + self.stime as u64, misc)
+    }
+
+    /// Convert clock ticks to a [Duration].
+    fn ticks(&self, ticks: u64, misc: &Misc) -> Option<Duration> {
+        let clk_tck = misc.clk_tck().filter(|tck| *tck != 0)? as u64;
+
+        Some(Duration::from_millis(ticks * 1000 / clk_tck))
+    }
+
+    /// Whether the process is a kernel thread (kworker, ksoftirqd, ...), as
+    /// opposed to a userspace process.
+    pub fn is_kernel_thread(&self) -> bool {
+        self.is_kernel_thread_in(self.namespaces.get(NamespaceKind::Pid))
+    }
+
+    /// Same as [Process::is_kernel_thread] before the namespaces are read,
+    /// the pid namespace is read only when it matters.
+    fn detect_kernel_thread(&self) -> bool {
+        let pid_ns = if self.pid == 2 || self.ppid == 2 {
+            Namespaces::from_pid(self.pid).get(NamespaceKind::Pid)
+        } else {
+            None
+        };
+
+        self.is_kernel_thread_in(pid_ns)
+    }
+
+    fn is_kernel_thread_in(&self, pid_ns: Option<u64>) -> bool {
+        if self.flags.flags().contains(ProcessFlags::KTHREAD) {
+            return true;
+        }
+
+        // kthreadd (pid 2) spawns all kernel threads, but pid 2 is an ordinary
+        // process in any other pid namespace, so this only applies when the
+        // pid namespace is known.
+        pid_ns == Some(namespace::PROC_PID_INIT_INO) && (self.pid == 2 || self.ppid == 2)
+    }
+
+    pub fn started_at(&self) -> Option<&str> {
+        self.started_at.as_deref()
+    }
+
+    /// Time the process started after system boot, in clock ticks.
+    pub fn starttime(&self) -> u64 {
+        self.starttime
+    }
+
+    /// Identity of the process, which unlike the pid is not reused.
+    pub fn id(&self) -> ProcessId {
+        ProcessId::new(self.pid, self.starttime)
+    }
+
+    pub fn namespaces(&self) -> &Namespaces {
+        &self.namespaces
+    }
+
+    pub fn nspid(&self) -> &[u32] {
+        &self.nspid
+    }
+
+    pub fn nstgid(&self) -> &[u32] {
+        &self.nstgid
+    }
 }
 
 /// Implementing Parser for [Processes] instead of [Process].
 impl PParser for Processes {
     fn parse() -> Result<Processes, DataError> {
-        Ok(fs::read_dir("/proc/")
-            .expect("")
-            .filter_map(|entry| {
-                let entry = entry.expect("");
-                let path = entry.path();
-
-                let name = path
-                    .metadata()
-                    .map(|metadata| uzers::get_user_by_uid(metadata.uid()))
-                    .map(|user| user.unwrap().name().to_str().unwrap().to_string())
-                    .ok();
-
-                let is_process = path.file_name().is_some_and(|folder_name| {
-                    // Verify if folder is all digits -> then its a process
-                    folder_name
-                        .to_str()
-                        .unwrap()
-                        .chars()
-                        .all(|c| c.is_ascii_digit())
-                });
-
-                if is_process {
-                    match &fs::read_to_string(entry.path().join("stat")) {
-                        Ok(str) => Process::new(str, name).ok(),
-                        Err(_) => None,
-                    }
-                } else {
-                    None
-                }
-            })
-            .collect())
+        parse_with(&ParseOptions::default())
     }
 }
 
+/// Collect the processes of the system, according to `options`.
+///
+/// Processes that could not be read are left out, use [iter] to handle them.
+pub fn parse_with(options: &ParseOptions) -> Result<Processes, DataError> {
+    Ok(iter(options.clone())?.filter_map(Result::ok).collect())
+}
+
+/// Lazily iterate over the processes of the system, according to `options`.
+pub fn iter(options: ParseOptions) -> Result<ProcessIter, DataError> {
+    let dir = fs::read_dir(PROC).map_err(|_| DataError::FileNotFound)?;
+    let misc = options.start_time.then(|| Misc::parse().ok()).flatten();
+
+    Ok(ProcessIter { dir, options, misc })
+}
+
+/// Iterator over the processes of `/proc`, created by [iter].
+///
+/// Every process is read when the iterator reaches it.
+pub struct ProcessIter {
+    dir: ReadDir,
+    options: ParseOptions,
+    misc: Option<Misc>,
+}
+
+impl ProcessIter {
+    fn read(&self, pid: u32, path: &Path) -> Result<Option<Process>, ProcessError> {
+        let stat = fs::read_to_string(path.join("stat")).map_err(|e| match e.kind() {
+            io::ErrorKind::PermissionDenied => ProcessError::PermissionDenied(pid),
+            _ => ProcessError::Vanished(pid),
+        })?;
+
+        // The stat file of an exiting process may be empty
+        if stat.is_empty() {
+            return Err(ProcessError::Vanished(pid));
+        }
+
+        let mut process = Process::new(&stat, None).map_err(|_| ProcessError::Parsing(pid))?;
+
+        if self.options.skip_kernel_threads && process.detect_kernel_thread() {
+            return Ok(None);
+        }
+
+        if self.options.user_name {
+            process.user_name = user_name(path);
+        }
+        process.read_extras(&self.options, self.misc.as_ref());
+
+        Ok(Some(process))
+    }
+}
+
+impl Iterator for ProcessIter {
+    type Item = Result<Process, ProcessError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Ok(entry) = self.dir.next()? else {
+                continue;
+            };
+
+            // Only folders made of digits are processes
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .filter(|name| name.chars().all(|c| c.is_ascii_digit()))
+                .and_then(|name| name.parse().ok())
+            else {
+                continue;
+            };
+
+            match self.read(pid, &entry.path()) {
+                Ok(Some(process)) => return Some(Ok(process)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+/// Name of the owner of a `/proc/[pid]` directory.
+fn user_name(path: &Path) -> Option<String> {
+    let metadata = path.metadata().ok()?;
+    let user = uzers::get_user_by_uid(metadata.uid())?;
+
+    user.name().to_str().map(|name| name.to_string())
+}
+
+/// Iterate over the userspace processes, leaving out kernel threads.
+pub fn userspace(processes: &[Process]) -> impl Iterator<Item = &Process> {
+    processes
+        .iter()
+        .filter(|process| !process.is_kernel_thread())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -237,6 +767,103 @@ mod test {
         Process::new("1 (systemd) S 0 1 1 0 -1 4194560 643700 109464643 189 69440 268 818 7489706 1155578 20 0 1 0 12 23293952 3585 18446744073709551615 1 1 0 0 0 0 671173123 4096 1260 0 0 0 17 5 0 0 0 0 0 0 0 0 0 0 0 0 0\n", Some("jeff".into())).expect("");
     }
 
+    #[test]
+    fn test_start_time() {
+        let processes = Processes::parse().unwrap();
+        let init = processes.iter().find(|p| p.pid == 1).unwrap();
+
+        assert_eq!(init.started_at(), Some("2023-11-14T22:13:20.120Z"));
+
+        // Hundredths of a second are kept after days of uptime
+        let misc = Misc::parse().unwrap();
+        let since_boot = 12 * 1000 / misc.clk_tck().unwrap() as u128;
+        assert_eq!(
+            init.run_time(&misc).unwrap().as_millis(),
+            101_822_330 - since_boot
+        );
+    }
+
+    #[test]
+    fn test_kernel_threads() {
+        let processes = Processes::parse().unwrap();
+        let kworker = processes.iter().find(|p| p.pid == 3).unwrap();
+
+        assert!(kworker.is_kernel_thread());
+        assert_eq!(userspace(&processes).count(), processes.len() - 1);
+
+        let options = ParseOptions {
+            skip_kernel_threads: true,
+            ..Default::default()
+        };
+        let processes = parse_with(&options).unwrap();
+
+        assert!(processes.iter().all(|p| !p.is_kernel_thread()));
+        assert!(processes.iter().any(|p| p.pid == 1));
+
+        // A child of pid 2 without PF_KTHREAD, e.g. in a container
+        let mut child = Process::new("14 (sh) S 2 14 14 0 -1 4194560 0 0 0 0 0 0 0 0 20 0 1 0 12 0 0 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 0 0 0 0 0 0 0 0 0 0 0 0 0 0\n", None).unwrap();
+        assert!(!child.is_kernel_thread());
+
+        child.namespaces = Namespaces::from_pid(1);
+        assert!(child.is_kernel_thread());
+
+        // Skipping while iterating agrees, before the namespaces are read
+        child.namespaces = Namespaces::default();
+        assert!(!child.detect_kernel_thread());
+        child.pid = 1;
+        assert!(child.detect_kernel_thread());
+        child.pid = 4242;
+        assert!(!child.detect_kernel_thread());
+    }
+
+    #[test]
+    fn test_from_pid_and_refresh() {
+        let mut process = Process::from_pid(4242).unwrap();
+        assert_eq!(process.nspid(), [4242, 1]);
+
+        process.utime = 0;
+        process.command.clear();
+        assert_eq!(process.refresh().unwrap(), RefreshStatus::Updated);
+        assert_eq!(process.utime, 12);
+        assert_eq!(process.nspid(), [4242, 1]);
+
+        // Parsing in place must agree with a full parse
+        let parsed = Process::read_stat(4242).unwrap();
+        assert_eq!(process.counters(), parsed.counters());
+        assert_eq!(process.command, parsed.command);
+        assert_eq!(process.signal, parsed.signal);
+        assert_eq!(process.exit, parsed.exit);
+
+        let minimal = Process::from_pid_with(4242, &ParseOptions::minimal(), None).unwrap();
+        assert_eq!(minimal.user_name, None);
+        assert!(minimal.nspid.is_empty());
+
+        process.starttime = 0;
+        assert_eq!(process.refresh().unwrap(), RefreshStatus::Replaced);
+
+        process.pid = 999999;
+        assert_eq!(process.refresh().unwrap(), RefreshStatus::Exited);
+
+        assert!(matches!(
+            Process::from_pid(999999),
+            Err(DataError::ProcessExited)
+        ));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut errors = vec![];
+        let processes: Processes = iter(ParseOptions::minimal())
+            .unwrap()
+            .filter_map(|res| res.map_err(|e| errors.push(e)).ok())
+            .collect();
+
+        assert_eq!(errors, vec![ProcessError::Parsing(13)]);
+        assert_eq!(processes.len(), 3);
+        assert!(processes.iter().all(|p| p.user_name.is_none()));
+        assert!(processes.iter().all(|p| p.nspid.is_empty()));
+    }
+
     #[test]
     fn test_parse_2() {
         Process::new("252201 (kworker/u33:3+i915_flip) D 2 0 0 0 -1 69238880 0 0 0 0 0 282 0 0 0 -20 1 0 16881972 0 0 18446744073709551615 0 0 0 0 0 0 0 2147483647 0 0 0 0 17 8 0 0 0 0 0 0 0 0 0 0 0 0 0\n", Some("jeff".into())).expect("");
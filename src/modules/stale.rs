@@ -0,0 +1,168 @@
+use crate::modules::{
+    cgroup::CGroup,
+    maps::{Mapping, MappingKind, MemoryMaps, DELETED},
+    process::{Process, PROC},
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, os::unix::fs::MetadataExt};
+
+/// A process running an executable or library that was deleted or replaced
+/// on disk since it was started, e.g. by a package upgrade.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct StaleProcess {
+    pid: u32,
+    command: String,
+    /// Path of the executable, if it is stale.
+    executable: Option<String>,
+    /// Paths of the stale shared libraries.
+    libraries: Vec<String>,
+    /// Systemd unit to restart, read from the cgroup of the process.
+    unit: Option<String>,
+}
+
+impl StaleProcess {
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    pub fn executable(&self) -> Option<&str> {
+        self.executable.as_deref()
+    }
+
+    pub fn libraries(&self) -> &[String] {
+        &self.libraries
+    }
+
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    /// Check a process for stale executable and libraries.
+    ///
+    /// Returns `None` if nothing is stale, or the process could not be inspected.
+    pub fn check(process: &Process) -> Option<StaleProcess> {
+        let pid = process.pid();
+
+        let exe = fs::read_link(format!("{}{}/exe", PROC, pid)).ok()?;
+        let exe = exe.to_str()?;
+        let executable = exe.strip_suffix(DELETED).map(|path| path.to_string());
+
+        let mut libraries: Vec<String> = MemoryMaps::from_pid(pid)
+            .map(|maps| {
+                maps.mappings()
+                    .iter()
+                    .filter(|mapping| is_stale(pid, mapping))
+                    .filter_map(|mapping| mapping.pathname())
+                    .filter(|path| Some(*path) != executable.as_deref() && *path != exe)
+                    .map(|path| path.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        libraries.sort_unstable();
+        libraries.dedup();
+
+        if executable.is_none() && libraries.is_empty() {
+            return None;
+        }
+
+        Some(StaleProcess {
+            pid,
+            command: process.command().to_string(),
+            executable,
+            libraries,
+            unit: CGroup::unit_of(pid),
+        })
+    }
+}
+
+/// Whether a mapping refers to code that is no longer the one on disk.
+fn is_stale(pid: u32, mapping: &Mapping) -> bool {
+    let Some(path) = mapping.pathname() else {
+        return false;
+    };
+
+    // Only code is relevant, memfd and SysV shared memory are always "deleted".
+    if *mapping.kind() != MappingKind::File
+        || !mapping.permissions().execute()
+        || path.starts_with("/memfd:")
+        || path.starts_with("/SYSV")
+    {
+        return false;
+    }
+
+    if mapping.deleted() {
+        return true;
+    }
+
+    // The file was replaced in place, look it up through the root of the
+    // process so that containerized processes are handled correctly. Inode
+    // numbers are only unique within a filesystem, so the device is compared
+    // as well.
+    fs::metadata(format!("{}{}/root{}", PROC, pid, path)).is_ok_and(|metadata| {
+        Some((metadata.dev(), metadata.ino())) != mapping.device().map(|dev| (dev, mapping.inode()))
+    })
+}
+
+/// List the processes that need a restart to use the current version of their
+/// executable and libraries.
+pub fn find(processes: &[Process]) -> Vec<StaleProcess> {
+    processes.iter().filter_map(StaleProcess::check).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{modules::process::Processes, parser::Parser};
+
+    #[test]
+    fn test_find() {
+        let processes = Processes::parse().unwrap();
+
+        let stale = find(&processes);
+
+        assert_eq!(
+            stale,
+            vec![StaleProcess {
+                pid: 4242,
+                command: "nginx".into(),
+                executable: Some("/usr/sbin/nginx".into()),
+                libraries: vec!["/usr/lib/x86_64-linux-gnu/libssl.so.3".into()],
+                unit: Some("docker-4f2a.scope".into()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let mapping = |line: &str| Mapping::parse(line).unwrap();
+
+        let deleted = mapping(
+            "558a3c040000-558a3c100000 r-xp 00040000 fd:01 1835210 /usr/sbin/nginx (deleted)",
+        );
+        assert!(is_stale(4242, &deleted));
+
+        let data = mapping(
+            "558a3c100000-558a3c140000 rw-p 00100000 fd:01 1835210 /usr/sbin/nginx (deleted)",
+        );
+        assert!(!is_stale(4242, &data));
+
+        let memfd =
+            mapping("7f3a1c000000-7f3a1c001000 r-xp 00000000 00:01 4242 /memfd:jit (deleted)");
+        assert!(!is_stale(4242, &memfd));
+
+        let metadata = fs::metadata("./mock/proc/3/root/usr/lib/libz.so.1").unwrap();
+        let (major, minor) = (libc::major(metadata.dev()), libc::minor(metadata.dev()));
+        let line = |major: u32, inode: u64| {
+            format!(
+                "7f3a1c000000-7f3a1c001000 r-xp 00000000 {:x}:{:x} {} /usr/lib/libz.so.1",
+                major, minor, inode
+            )
+        };
+
+        assert!(!is_stale(3, &mapping(&line(major, metadata.ino()))));
+        // replaced in place
+        assert!(is_stale(3, &mapping(&line(major, metadata.ino() + 1))));
+        // same inode number on another filesystem
+        assert!(is_stale(3, &mapping(&line(major + 1, metadata.ino()))));
+    }
+}
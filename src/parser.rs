@@ -2,6 +2,14 @@
 pub enum DataError {
     Parsing,
     FileNotFound,
+    /// The process has exited.
+    ProcessExited,
+    /// The pid now belongs to another process.
+    ProcessReplaced,
+    /// Not allowed to act on the process.
+    PermissionDenied,
+    /// The signal is not supported by the kernel.
+    InvalidSignal,
 }
 
 pub trait Parser {